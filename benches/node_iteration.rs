@@ -1,7 +1,3 @@
-#![feature(sync_unsafe_cell)]
-#![feature(ptr_as_ref_unchecked)]
-#![feature(downcast_unchecked)]
-
 use criterion::{Criterion, criterion_group, criterion_main};
 use necs_internal::*;
 use necs_macros::node;
@@ -17,9 +13,7 @@ struct Foo {
 fn criterion_benchmark(c: &mut Criterion) {
     let mut world = World::new();
     world.register_node::<Foo>();
-    for _ in 0..1_000_000 {
-        world.spawn_node(FooBuilder { a: 1, b: 2, c: 3 });
-    }
+    world.spawn_batch((0..1_000_000).map(|_| FooBuilder { a: 1, b: 2, c: 3 }));
 
     c.bench_function("node_iteration", |b| {
         b.iter(|| {
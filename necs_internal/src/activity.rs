@@ -0,0 +1,144 @@
+use crate::{NecsError, NodeId, Relations, World};
+use std::vec;
+
+/// Decides whether a node is processed while the [`World`] is paused, see
+/// [`World::set_paused`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ProcessMode {
+    /// Uses the process mode of the parent, or [`ProcessMode::Pausable`] for
+    /// root nodes.
+    #[default]
+    Inherit,
+    /// Only active while the world is not paused.
+    Pausable,
+    /// Only active while the world is paused, for example for pause menus.
+    WhenPaused,
+    /// Active whether the world is paused or not.
+    Always,
+}
+
+/// The ids yielded by [`World::get_node_ids`], which only need to be filtered
+/// if some node may be inactive.
+pub(crate) enum ActiveIds<I> {
+    All(I),
+    Active(vec::IntoIter<NodeId>),
+}
+
+impl<I: Iterator<Item = NodeId>> Iterator for ActiveIds<I> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        match self {
+            Self::All(ids) => ids.next(),
+            Self::Active(ids) => ids.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::All(ids) => ids.size_hint(),
+            Self::Active(ids) => ids.size_hint(),
+        }
+    }
+}
+
+impl<I: ExactSizeIterator<Item = NodeId>> ExactSizeIterator for ActiveIds<I> {}
+
+impl World {
+    /// Enables or disables the node along with its whole subtree.
+    ///
+    /// Inactive nodes are skipped by [`get_nodes`](World::get_nodes) and
+    /// [`get_node_ids`](World::get_node_ids), but can still be accessed by
+    /// id.
+    ///
+    /// # Errors
+    /// Fails if the node does not exist.
+    pub fn set_enabled(&mut self, id: NodeId, enabled: bool) -> Result<(), NecsError> {
+        self.storage.nodes.check(id)?;
+        self.update_flags(id, |relations| relations.set_enabled(enabled));
+        Ok(())
+    }
+
+    /// Sets the [`ProcessMode`] of the node, which is inherited by descendants
+    /// using [`ProcessMode::Inherit`].
+    ///
+    /// # Errors
+    /// Fails if the node does not exist.
+    pub fn set_process_mode(&mut self, id: NodeId, mode: ProcessMode) -> Result<(), NecsError> {
+        self.storage.nodes.check(id)?;
+        self.update_flags(id, |relations| relations.set_process_mode(mode));
+        Ok(())
+    }
+
+    /// Pauses or unpauses the world, deactivating or activating nodes
+    /// depending on their [`ProcessMode`].
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns whether the node exists, neither it nor any ancestor is
+    /// disabled, and its [`ProcessMode`] allows it to be processed.
+    pub fn is_active(&self, id: NodeId) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+        if self.all_active() {
+            return true;
+        }
+        let mut mode = ProcessMode::Inherit;
+        let mut key = Some(id.instance);
+        while let Some(relations) = key.and_then(|key| self.community.get(&key)) {
+            if !relations.enabled() {
+                return false;
+            }
+            if mode == ProcessMode::Inherit {
+                mode = relations.process_mode();
+            }
+            key = relations.parent();
+        }
+        match mode {
+            ProcessMode::Inherit | ProcessMode::Pausable => !self.paused,
+            ProcessMode::WhenPaused => self.paused,
+            ProcessMode::Always => true,
+        }
+    }
+
+    /// Returns whether every node is known to be active without looking at
+    /// them.
+    pub(crate) fn all_active(&self) -> bool {
+        self.flagged_nodes == 0 && !self.paused
+    }
+
+    /// Only keeps the active ids, unless every node is active anyway.
+    pub(crate) fn active_ids<I>(&self, ids: I) -> ActiveIds<I>
+    where
+        I: Iterator<Item = NodeId>,
+    {
+        if self.all_active() {
+            return ActiveIds::All(ids);
+        }
+        ActiveIds::Active(
+            ids.filter(|&id| self.is_active(id))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        )
+    }
+
+    /// Updates the flags of a node, keeping track of how many nodes have
+    /// flags that may make them inactive.
+    fn update_flags(&mut self, id: NodeId, update: impl FnOnce(&mut Relations)) {
+        let relations = self.relations_mut(id.instance);
+        let was_flagged = relations.is_flagged();
+        update(relations);
+        let is_flagged = relations.is_flagged();
+        match (was_flagged, is_flagged) {
+            (false, true) => self.flagged_nodes += 1,
+            (true, false) => self.flagged_nodes -= 1,
+            _ => {}
+        }
+    }
+}
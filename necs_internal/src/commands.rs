@@ -0,0 +1,115 @@
+use crate::{NecsError, NodeBuilder, NodeId, World};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Mutex, PoisonError};
+
+type Command = Box<dyn FnOnce(&mut World) -> Result<(), NecsError> + Send>;
+
+/// Commands recorded through [`Commands`], waiting to be applied by
+/// [`World::apply_commands`].
+#[derive(Default)]
+pub(crate) struct CommandQueue(Mutex<Vec<Command>>);
+
+impl Debug for CommandQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let len = self.0.lock().unwrap_or_else(PoisonError::into_inner).len();
+        write!(f, "[{} pending]", len)
+    }
+}
+
+impl CommandQueue {
+    fn push(&self, command: Command) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(command);
+    }
+
+    /// Takes every pending command, leaving the queue empty.
+    pub(crate) fn take(&mut self) -> Vec<Command> {
+        std::mem::take(self.0.get_mut().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Records structural changes to a [`World`] while it is only borrowed
+/// immutably, for example while iterating over nodes.
+///
+/// Recorded commands are applied in order by [`World::apply_commands`].
+/// [`Commands`] is [`Copy`] and [`Send`], so it may be handed to other
+/// threads.
+/// ```
+/// use necs::{World, node};
+/// let mut world = World::new();
+///
+/// #[node]
+/// struct Gun {
+///     ammo: u32,
+/// }
+///
+/// #[node]
+/// struct Bullet;
+///
+/// world.register_node::<Gun>();
+/// world.register_node::<Bullet>();
+/// world.spawn_node(GunBuilder { ammo: 2 });
+///
+/// let commands = world.commands();
+/// for gun in world.get_nodes::<Gun>() {
+///     *gun.ammo -= 1;
+///     commands.spawn(BulletBuilder);
+/// }
+///
+/// world.apply_commands().unwrap();
+/// assert_eq!(world.get_node_ids::<Bullet>().len(), 1);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Commands<'world> {
+    queue: &'world CommandQueue,
+}
+
+impl<'world> Commands<'world> {
+    pub(crate) fn new(queue: &'world CommandQueue) -> Self {
+        Self { queue }
+    }
+
+    /// Records spawning the given node, see [`World::spawn_node`].
+    pub fn spawn<T>(&self, node: T)
+    where
+        T: NodeBuilder + Send + 'static,
+    {
+        self.add(move |world| {
+            world.spawn_node(node);
+            Ok(())
+        });
+    }
+
+    /// Records despawning the given node, see [`World::despawn_node`].
+    pub fn despawn(&self, id: NodeId) {
+        self.add(move |world| world.despawn_node(id));
+    }
+
+    /// Records despawning the given node along with its descendants, see
+    /// [`World::despawn_recursive`].
+    pub fn despawn_recursive(&self, id: NodeId) {
+        self.add(move |world| world.despawn_recursive(id));
+    }
+
+    /// Records making `child` a child of `parent`, see [`World::set_parent`].
+    pub fn set_parent(&self, child: NodeId, parent: NodeId) {
+        self.add(move |world| world.set_parent(child, parent));
+    }
+
+    /// Records detaching `child` from its parent, see
+    /// [`World::remove_parent`].
+    pub fn remove_parent(&self, child: NodeId) {
+        self.add(move |world| world.remove_parent(child));
+    }
+
+    /// Records an arbitrary change to the [`World`], such as writing to a
+    /// node's `#[ext]` fields.
+    pub fn add<F>(&self, command: F)
+    where
+        F: FnOnce(&mut World) -> Result<(), NecsError> + Send + 'static,
+    {
+        self.queue.push(Box::new(command));
+    }
+}
@@ -1,6 +1,7 @@
-use super::{ItemKey, storage::MiniTypeId};
+use super::{BorrowDropper, ItemKey, storage::MiniTypeId};
 use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 
 /// A wrapper around [`ItemKey`], along with [`T`] and [`MiniTypeId`] for
 /// efficient downcasting.
@@ -32,6 +33,11 @@ impl<T> ComponentId<T> {
     /// expectations can result in a panic where no entry of [`ItemKey`] can be
     /// found for [`T`].
     ///
+    /// # Safety
+    ///
+    /// `component_type` must be the [`MiniTypeId`] that component storage
+    /// assigned to [`T`], as it is later used to downcast without checks.
+    ///
     /// # Examples
     ///
     /// ```
@@ -66,3 +72,41 @@ impl<T> From<&ComponentId<T>> for ItemKey {
         component_id.key
     }
 }
+
+/// An `#[ext]` field yielded by
+/// [`World::components`](crate::World::components).
+///
+/// The node the field belongs to stays borrowed until this is dropped.
+pub struct ComponentMut<'a, T> {
+    component: &'a mut T,
+    _borrow_dropper: BorrowDropper<'a>,
+}
+
+impl<'a, T> ComponentMut<'a, T> {
+    pub(crate) fn new(component: &'a mut T, borrow_dropper: BorrowDropper<'a>) -> Self {
+        Self {
+            component,
+            _borrow_dropper: borrow_dropper,
+        }
+    }
+}
+
+impl<T: Debug> Debug for ComponentMut<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.component, f)
+    }
+}
+
+impl<T> Deref for ComponentMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.component
+    }
+}
+
+impl<T> DerefMut for ComponentMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.component
+    }
+}
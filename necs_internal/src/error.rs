@@ -0,0 +1,87 @@
+use crate::NodeId;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Errors returned by fallible [`World`](crate::World) operations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NecsError {
+    /// The type with the given name was never registered.
+    NotRegistered(&'static str),
+    /// The node does not exist, most likely because it was despawned.
+    StaleId(NodeId),
+    /// The node exists, but its type was changed with
+    /// [`morph_node`](crate::World::morph_node), so it now goes by `current`.
+    TypeChanged { id: NodeId, current: NodeId },
+    /// The node was taken out of the world with
+    /// [`take_node`](crate::World::take_node) and not reinserted yet.
+    Taken(NodeId),
+    /// The node was expected to be taken out of the world, but is still in it.
+    NotTaken(NodeId),
+    /// The node is currently borrowed, for example through a live
+    /// [`get_node_resilient`](crate::World::get_node_resilient) handle.
+    AlreadyBorrowed(NodeId),
+    /// The node exists, but is not of the requested concrete type.
+    WrongNodeType { id: NodeId, expected: &'static str },
+    /// The node's type was not registered as implementing the given trait.
+    TraitNotImplemented {
+        id: NodeId,
+        trait_name: &'static str,
+    },
+    /// The node's type does not implement [`CloneNode`](crate::CloneNode) or
+    /// was not detected as doing so when registered.
+    NotCloneable(NodeId),
+    /// The node is not a child of `parent`.
+    NotChild { id: NodeId, parent: NodeId },
+    /// Making the node a child of `parent` would make it its own ancestor.
+    WouldCycle { id: NodeId, parent: NodeId },
+    /// A sibling of the node already has the name it was supposed to get, see
+    /// [`set_name`](crate::World::set_name). Holds the sibling's id.
+    NameTaken(NodeId),
+    /// The name is empty, contains a `/`, or is `.` or `..`.
+    InvalidName,
+    /// A [`Field`](crate::Field) was downcast to a type it does not have.
+    WrongFieldType(&'static str),
+}
+
+impl Display for NecsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRegistered(type_name) => write!(f, "type {} is not registered", type_name),
+            Self::StaleId(id) => write!(
+                f,
+                "stale id {:?}, the node was despawned or never existed",
+                id
+            ),
+            Self::TypeChanged { id, current } => {
+                write!(f, "node {:?} changed type, it is now {:?}", id, current)
+            }
+            Self::Taken(id) => write!(f, "node {:?} was taken out of the world", id),
+            Self::NotTaken(id) => write!(f, "node {:?} was not taken out of the world", id),
+            Self::AlreadyBorrowed(id) => write!(f, "node {:?} is currently borrowed", id),
+            Self::WrongNodeType { id, expected } => {
+                write!(f, "node {:?} is not of type {}", id, expected)
+            }
+            Self::TraitNotImplemented { id, trait_name } => write!(
+                f,
+                "node {:?} is not registered for trait {}",
+                id, trait_name
+            ),
+            Self::NotCloneable(id) => write!(f, "node {:?} is not of a cloneable type", id),
+            Self::NotChild { id, parent } => {
+                write!(f, "node {:?} is not a child of {:?}", id, parent)
+            }
+            Self::WouldCycle { id, parent } => write!(
+                f,
+                "node {:?} can't be a child of its descendant {:?}",
+                id, parent
+            ),
+            Self::NameTaken(id) => write!(f, "sibling {:?} already has this name", id),
+            Self::InvalidName => {
+                write!(f, "node names must not be empty, `.`, `..` or contain `/`")
+            }
+            Self::WrongFieldType(type_name) => write!(f, "invalid downcast to {}", type_name),
+        }
+    }
+}
+
+impl Error for NecsError {}
@@ -0,0 +1,191 @@
+use crate::{NecsError, NodeId, NodeTrait, World};
+use std::any::{Any, TypeId};
+use std::ops::{Deref, DerefMut};
+
+/// A handler added to a single node with [`World::add_event_handler`].
+type EventHandler<E> = Box<dyn Fn(&mut Event<'_, E>) + Send + Sync>;
+/// An [`EventHandler`] along with the [`TypeId`] of the events it handles.
+pub(crate) type ErasedEventHandler = (TypeId, Box<dyn Any + Send + Sync>);
+
+/// Implement this on nodes that should handle events of type `E` no matter
+/// which node they are, and register them for `dyn Handles<E>` with
+/// [`World::register_trait`].
+pub trait Handles<E: 'static>: NodeTrait {
+    fn handle(&mut self, event: &mut Event<'_, E>);
+}
+
+/// Which part of its path an event dispatched with [`World::dispatch_event`]
+/// is currently in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EventPhase {
+    /// Going down from the root to the parent of the target.
+    Capture,
+    /// At the target itself.
+    Target,
+    /// Going back up from the parent of the target to the root.
+    Bubble,
+}
+
+/// An event being dispatched with [`World::dispatch_event`], dereferencing to
+/// its payload.
+#[derive(Debug)]
+pub struct Event<'a, E> {
+    world: &'a World,
+    payload: &'a mut E,
+    target: NodeId,
+    current: NodeId,
+    phase: EventPhase,
+    stopped: bool,
+}
+
+impl<'a, E> Event<'a, E> {
+    /// Returns the [`World`] the event is dispatched in. Structural changes
+    /// can be recorded through [`World::commands`].
+    pub fn world(&self) -> &'a World {
+        self.world
+    }
+
+    /// Returns the node the event was dispatched to.
+    pub fn target(&self) -> NodeId {
+        self.target
+    }
+
+    /// Returns the node whose handlers are currently being invoked.
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn phase(&self) -> EventPhase {
+        self.phase
+    }
+
+    /// Prevents the event from reaching any further nodes. Remaining handlers
+    /// of the current node are still invoked.
+    pub fn stop_propagation(&mut self) {
+        self.stopped = true;
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+impl<E> Deref for Event<'_, E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        self.payload
+    }
+}
+
+impl<E> DerefMut for Event<'_, E> {
+    fn deref_mut(&mut self) -> &mut E {
+        self.payload
+    }
+}
+
+impl World {
+    /// Adds a handler for events of type `E` reaching the given node, which is
+    /// invoked before the node's own [`Handles<E>`] implementation, if any.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::StaleId`] if the node does not exist.
+    pub fn add_event_handler<E, F>(&mut self, id: NodeId, handler: F) -> Result<(), NecsError>
+    where
+        E: 'static,
+        F: Fn(&mut Event<'_, E>) + Send + Sync + 'static,
+    {
+        self.storage.nodes.check(id)?;
+        let handler: EventHandler<E> = Box::new(handler);
+        self.event_handlers
+            .entry(id.instance)
+            .or_default()
+            .push((TypeId::of::<E>(), Box::new(handler)));
+        Ok(())
+    }
+
+    /// Removes every handler for events of type `E` added to the given node
+    /// with [`add_event_handler`](World::add_event_handler).
+    pub fn remove_event_handlers<E: 'static>(&mut self, id: NodeId) {
+        if !self.contains(id) {
+            return;
+        }
+        if let Some(handlers) = self.event_handlers.get_mut(&id.instance) {
+            handlers.retain(|(event_type, _)| *event_type != TypeId::of::<E>());
+        }
+    }
+
+    /// Dispatches an event to `target`, first going down from its root in the
+    /// [`EventPhase::Capture`] phase, then reaching the target itself, and
+    /// finally bubbling back up to the root, until a handler calls
+    /// [`Event::stop_propagation`].
+    ///
+    /// At every node, the handlers added with
+    /// [`add_event_handler`](World::add_event_handler) are invoked in order,
+    /// followed by the node's [`Handles<E>`] implementation if its type was
+    /// registered for `dyn Handles<E>`.
+    ///
+    /// Nodes that were taken with [`take_node`](World::take_node) are skipped.
+    ///
+    /// # Errors
+    /// Fails if `target` does not exist, or with
+    /// [`NecsError::AlreadyBorrowed`] if a node of the path implementing
+    /// [`Handles<E>`] is currently borrowed, in which case no handler is
+    /// invoked.
+    pub fn dispatch_event<E: 'static>(
+        &self,
+        target: NodeId,
+        event: &mut E,
+    ) -> Result<(), NecsError> {
+        self.storage.nodes.check(target)?;
+        let ancestors: Vec<NodeId> = self.ancestors(target).collect();
+        for &id in ancestors.iter().chain([&target]) {
+            if self.trait_map.implements::<dyn Handles<E>>(id.node_type) && self.is_borrowed(id) {
+                return Err(NecsError::AlreadyBorrowed(id));
+            }
+        }
+        let path = ancestors
+            .iter()
+            .rev()
+            .map(|&id| (id, EventPhase::Capture))
+            .chain([(target, EventPhase::Target)])
+            .chain(ancestors.iter().map(|&id| (id, EventPhase::Bubble)));
+
+        let mut event = Event {
+            world: self,
+            payload: event,
+            target,
+            current: target,
+            phase: EventPhase::Target,
+            stopped: false,
+        };
+        for (id, phase) in path {
+            event.current = id;
+            event.phase = phase;
+            self.invoke_handlers(&mut event)?;
+            if event.stopped {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Invokes every handler of the current node of the event.
+    fn invoke_handlers<E: 'static>(&self, event: &mut Event<'_, E>) -> Result<(), NecsError> {
+        let handlers = self.event_handlers.get(&event.current.instance);
+        for (event_type, handler) in handlers.into_iter().flatten() {
+            if *event_type == TypeId::of::<E>() {
+                let handler = handler
+                    .downcast_ref::<EventHandler<E>>()
+                    .expect("handler should be of the right type");
+                handler(event);
+            }
+        }
+        match self.try_get_node_resilient::<dyn Handles<E>>(event.current) {
+            Ok(mut node) => node.handle(event),
+            Err(NecsError::TraitNotImplemented { .. } | NecsError::Taken(_)) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+}
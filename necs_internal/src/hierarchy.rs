@@ -0,0 +1,291 @@
+use crate::{ItemKey, NecsError, NodeBuilder, NodeId, Relations, World};
+use rustc_hash::FxHashMap as HashMap;
+
+/// What happens to the descendants of a node despawned with
+/// [`World::despawn_with`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DespawnPolicy {
+    /// Children become root nodes, like with [`World::despawn_node`].
+    #[default]
+    Orphan,
+    /// Every descendant is despawned as well, like with
+    /// [`World::despawn_recursive`].
+    Recursive,
+    /// Children take the place of the despawned node among the children of its
+    /// parent, keeping their order, or become root nodes if it had none.
+    Reparent,
+}
+
+impl World {
+    /// Spawns a node as the last child of `parent`.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::StaleId`] if `parent` does not exist, in which
+    /// case nothing is spawned.
+    pub fn spawn_child<T: NodeBuilder>(
+        &mut self,
+        parent: NodeId,
+        node: T,
+    ) -> Result<NodeId, NecsError> {
+        self.storage.nodes.check(parent)?;
+        let child = self.spawn_detached(node);
+        self.attach(child.instance, parent.instance);
+        self.enter_tree(child, true);
+        Ok(child)
+    }
+
+    /// Makes `child` the last child of `parent`, detaching it from its
+    /// previous parent if it had one.
+    ///
+    /// Nothing changes if `child` already is a child of `parent`.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::StaleId`] if either node does not exist, with
+    /// [`NecsError::WouldCycle`] if `parent` is `child` or one of its
+    /// descendants, with [`NecsError::NameTaken`] if a child of `parent`
+    /// already has the name of `child`, or with [`NecsError::AlreadyBorrowed`]
+    /// if a node of the subtree of `child` whose
+    /// [`Lifecycle`](crate::Lifecycle) callbacks would be invoked is
+    /// currently borrowed.
+    pub fn set_parent(&mut self, child: NodeId, parent: NodeId) -> Result<(), NecsError> {
+        self.storage.nodes.check(child)?;
+        self.storage.nodes.check(parent)?;
+        if self.relations_mut(child.instance).parent() == Some(parent.instance) {
+            return Ok(());
+        }
+        self.insert_child_at(parent, child, usize::MAX)
+    }
+
+    /// Makes `child` a child of `parent` at the given position among its
+    /// siblings, detaching it from its previous parent if it had one. Indices
+    /// past the end insert it as the last child.
+    ///
+    /// If `child` already is a child of `parent`, it is moved like with
+    /// [`move_child`](World::move_child).
+    ///
+    /// # Errors
+    /// Fails like [`set_parent`](World::set_parent).
+    pub fn insert_child_at(
+        &mut self,
+        parent: NodeId,
+        child: NodeId,
+        index: usize,
+    ) -> Result<(), NecsError> {
+        self.storage.nodes.check(child)?;
+        self.storage.nodes.check(parent)?;
+        if self.parent_of(child) == Some(parent) {
+            return self.move_child(parent, child, index);
+        }
+        if parent == child || self.ancestors(parent).any(|ancestor| ancestor == child) {
+            return Err(NecsError::WouldCycle { id: child, parent });
+        }
+        let sibling = self
+            .name_of(child)
+            .and_then(|name| self.child_named(parent.instance, name));
+        if let Some(sibling) = sibling {
+            return Err(NecsError::NameTaken(sibling));
+        }
+        self.check_lifecycle(child)?;
+        self.exit_tree(child);
+        self.detach(child.instance);
+        self.attach_at(child.instance, parent.instance, index);
+        self.enter_tree(child, false);
+        Ok(())
+    }
+
+    /// Moves `child` to the given position among the children of `parent`,
+    /// shifting its siblings. Indices past the end make it the last child.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::StaleId`] if either node does not exist, or
+    /// with [`NecsError::NotChild`] if `child` is not a child of `parent`.
+    pub fn move_child(
+        &mut self,
+        parent: NodeId,
+        child: NodeId,
+        index: usize,
+    ) -> Result<(), NecsError> {
+        self.storage.nodes.check(child)?;
+        self.storage.nodes.check(parent)?;
+        if self.parent_of(child) != Some(parent) {
+            return Err(NecsError::NotChild { id: child, parent });
+        }
+        let relations = self.relations_mut(parent.instance);
+        relations.remove_child(child.instance);
+        relations.insert_child(index, child.instance);
+        Ok(())
+    }
+
+    /// Returns the position of the node among the children of its parent, or
+    /// [`None`] if it is a root node or does not exist.
+    pub fn child_index(&self, id: NodeId) -> Option<usize> {
+        let parent = self.parent_of(id)?;
+        self.community[&parent.instance]
+            .children()
+            .iter()
+            .position(|&child| child == id.instance)
+    }
+
+    /// Detaches `child` from its parent, making it a root node.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::StaleId`] if `child` does not exist, or like
+    /// [`set_parent`](World::set_parent) if a node of its subtree is
+    /// borrowed.
+    pub fn remove_parent(&mut self, child: NodeId) -> Result<(), NecsError> {
+        self.storage.nodes.check(child)?;
+        if self.parent_of(child).is_some() {
+            self.check_lifecycle(child)?;
+            self.exit_tree(child);
+            self.detach(child.instance);
+            self.enter_tree(child, false);
+        }
+        Ok(())
+    }
+
+    /// Returns the parent of the given node, or [`None`] if it is a root node
+    /// or does not exist.
+    pub fn parent_of(&self, id: NodeId) -> Option<NodeId> {
+        if !self.contains(id) {
+            return None;
+        }
+        let parent = self.community.get(&id.instance)?.parent()?;
+        self.storage.nodes.id_of(parent)
+    }
+
+    /// Iterates over the children of the given node in order, yielding nothing
+    /// if it does not exist.
+    pub fn children_of(&self, id: NodeId) -> impl ExactSizeIterator<Item = NodeId> + '_ {
+        let children = match self.community.get(&id.instance) {
+            Some(relations) if self.contains(id) => relations.children(),
+            _ => &[],
+        };
+        children.iter().map(|&child| {
+            self.storage
+                .nodes
+                .id_of(child)
+                .expect("children should always be alive")
+        })
+    }
+
+    /// Removes the node associated with the given [`NodeId`] along with all of
+    /// its descendants, see [`despawn_node`](World::despawn_node).
+    ///
+    /// # Errors
+    /// Fails if the node does not exist, or with
+    /// [`NecsError::AlreadyBorrowed`] if it or any of its descendants is
+    /// currently borrowed, in which case nothing is removed.
+    pub fn despawn_recursive(&mut self, id: NodeId) -> Result<(), NecsError> {
+        // Collect the subtree with parents before their children, making sure nothing
+        // is borrowed before removing anything.
+        let mut subtree = vec![id];
+        let mut i = 0;
+        while let Some(&node_id) = subtree.get(i) {
+            self.check_removable(node_id)?;
+            subtree.extend(self.children_of(node_id));
+            i += 1;
+        }
+        self.exit_tree(id);
+        for node_id in subtree.into_iter().rev() {
+            self.remove_node(node_id)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the node associated with the given [`NodeId`], handling its
+    /// descendants according to `policy`.
+    ///
+    /// # Errors
+    /// Fails like [`despawn_node`](World::despawn_node) or
+    /// [`despawn_recursive`](World::despawn_recursive), in which case nothing
+    /// is removed. [`DespawnPolicy::Reparent`] also fails with
+    /// [`NecsError::NameTaken`] if a child's name is already taken among the
+    /// children of the node's parent.
+    pub fn despawn_with(&mut self, id: NodeId, policy: DespawnPolicy) -> Result<(), NecsError> {
+        match policy {
+            DespawnPolicy::Orphan => self.despawn_node(id),
+            DespawnPolicy::Recursive => self.despawn_recursive(id),
+            DespawnPolicy::Reparent => {
+                let parent = self.parent_of(id);
+                let children: Vec<NodeId> = self.children_of(id).collect();
+                if let Some(parent) = parent {
+                    for &child in &children {
+                        let sibling = self
+                            .name_of(child)
+                            .and_then(|name| self.child_named(parent.instance, name));
+                        if let Some(sibling) = sibling.filter(|&sibling| sibling != id) {
+                            return Err(NecsError::NameTaken(sibling));
+                        }
+                    }
+                }
+                let index = self.child_index(id);
+                self.check_removable(id)?;
+                self.check_lifecycle(id)?;
+                self.exit_tree(id);
+                self.remove_node(id)?;
+                if let (Some(parent), Some(index)) = (parent, index) {
+                    for (i, &child) in children.iter().enumerate() {
+                        self.attach_at(child.instance, parent.instance, index + i);
+                    }
+                }
+                for child in children {
+                    self.enter_tree(child, false);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Makes `child`, which must not have a parent, the last child of `parent`.
+    fn attach(&mut self, child: ItemKey, parent: ItemKey) {
+        self.attach_at(child, parent, usize::MAX);
+    }
+
+    /// Makes `child`, which must not have a parent, a child of `parent` at the
+    /// given position, or the last child if it is past the end.
+    fn attach_at(&mut self, child: ItemKey, parent: ItemKey, index: usize) {
+        self.propagation_caches.invalidate(child);
+        self.relations_mut(child).set_parent(Some(parent));
+        self.relations_mut(parent).insert_child(index, child);
+    }
+
+    /// Removes `child` from the children of its parent, if it has one.
+    pub(crate) fn detach(&mut self, child: ItemKey) {
+        let Some(parent) = self.relations_mut(child).parent() else {
+            return;
+        };
+        self.propagation_caches.invalidate(child);
+        self.relations_mut(child).set_parent(None);
+        if let Some(parent) = self.community.get_mut(&parent) {
+            parent.remove_child(child);
+        }
+    }
+
+    /// Returns the [`Relations`] of every node.
+    pub fn community(&self) -> &HashMap<ItemKey, Relations> {
+        &self.community
+    }
+
+    /// Gives direct access to the [`Relations`] of every node, which is
+    /// unchecked and can make the hierarchy inconsistent, see
+    /// [`validate`](World::validate).
+    pub fn edit_community<R>(
+        &mut self,
+        edit: impl FnOnce(&mut HashMap<ItemKey, Relations>) -> R,
+    ) -> R {
+        let result = edit(&mut self.community);
+        // Flags may have been changed or dropped along with any entry.
+        self.flagged_nodes = (self.community.values())
+            .filter(|relations| relations.is_flagged())
+            .count();
+        result
+    }
+
+    /// Gets the [`Relations`] of the given node, creating them if they were
+    /// removed from [`community`](World::community).
+    pub(crate) fn relations_mut(&mut self, key: ItemKey) -> &mut Relations {
+        self.community
+            .entry(key)
+            .or_insert_with(|| Relations::new(None))
+    }
+}
@@ -1,26 +1,50 @@
 #![feature(downcast_unchecked)]
-#![feature(unsafe_cell_access)]
 #![feature(sync_unsafe_cell)]
-#![feature(ptr_as_ref_unchecked)]
 
-use rustc_hash::FxHashMap as HashMap;
-pub use crate::node::{Field, NodeBuilder, NodeId, NodeRef, NodeTrait};
+use crate::commands::CommandQueue;
+use crate::events::ErasedEventHandler;
+pub use crate::node::{CloneNode, Field, NodeBuilder, NodeId, NodeRef, NodeTrait};
+#[doc(hidden)]
+pub use crate::node::{CloneProbe, ViaCloneNode, ViaNoCloneNode};
+use crate::propagate::PropagationCaches;
 use crate::trait_map::TraitMap;
 pub use necs_macros::node;
+use rustc_hash::FxHashMap as HashMap;
 use slotmap::SparseSecondaryMap;
-use storage::Storage;
+use std::any::type_name;
+use storage::{MiniTypeId, RecipeTupleCell, Storage};
 
+mod activity;
+mod commands;
 mod component;
+mod error;
+mod events;
+mod hierarchy;
+mod lifecycle;
 pub use crate::node::Node;
-pub use component::ComponentId;
+pub use activity::ProcessMode;
+pub use commands::Commands;
+pub use component::{ComponentId, ComponentMut};
+pub use error::NecsError;
+pub use events::{Event, EventPhase, Handles};
+pub use hierarchy::DespawnPolicy;
+pub use lifecycle::Lifecycle;
+pub use query::{Fetch, NodeQuery, Query};
+pub use relations::Relations;
 pub use storage::BorrowDropper;
 pub use storage::ItemKey;
-pub use relations::Relations;
+pub use traversal::{Ancestors, Descendants, DescendantsBfs, Siblings, TreeIterator};
+pub use validation::{HierarchyIssue, ValidationReport};
 
 mod node;
+mod path;
+mod propagate;
+mod query;
+mod relations;
 pub mod storage;
 mod trait_map;
-mod relations;
+mod traversal;
+mod validation;
 
 pub type SubStorage<T> = SparseSecondaryMap<ItemKey, T>;
 
@@ -31,7 +55,18 @@ pub struct World {
     // Maps TypeIds to types, allowing us to work on nodes without knowing their types.
     trait_map: TraitMap,
     // TODO: I should really give this a better name.
-    pub community: HashMap<ItemKey, Relations>,
+    pub(crate) community: HashMap<ItemKey, Relations>,
+    // Changes recorded through Commands, applied by apply_commands().
+    commands: CommandQueue,
+    // What propagate() saw last time for each pair of component types.
+    propagation_caches: PropagationCaches,
+    // Handlers added with add_event_handler(), along with the type of event they handle.
+    event_handlers: HashMap<ItemKey, Vec<ErasedEventHandler>>,
+    // Whether the world is paused, see ProcessMode.
+    paused: bool,
+    // How many nodes are disabled or have a process mode, so that we can skip checking
+    // whether nodes are active when there are none.
+    flagged_nodes: usize,
 }
 
 impl World {
@@ -39,6 +74,12 @@ impl World {
         Self::default()
     }
 
+    /// Registers a node type along with its `#[ext]` fields, and registers it
+    /// as implementing [Node].
+    ///
+    /// Node types are registered automatically the first time they are
+    /// spawned, so this is only needed to avoid paying for registration
+    /// later.
     pub fn register_node<T>(&mut self)
     where
         T: NodeRef,
@@ -47,27 +88,377 @@ impl World {
         self.trait_map
             .register::<T, dyn Node, _>(self.storage.nodes.mini_type_of::<T>(), |x| Box::new(x));
     }
+    /// Despawns every node of type [T] and unregisters the type, including
+    /// from every trait it was registered for.
+    ///
+    /// Afterwards, accessing [T] fails as if it was never registered, and ids
+    /// of its nodes are stale. Spawning [T] again registers it anew.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::NotRegistered`] if [T] is not registered, or
+    /// with [`NecsError::AlreadyBorrowed`] if any node of type [T], or a node
+    /// whose [`Lifecycle`] callbacks despawning them would invoke, is currently
+    /// borrowed, in which case nothing is despawned.
+    pub fn unregister_node<T: NodeRef>(&mut self) -> Result<(), NecsError> {
+        let node_type = self
+            .storage
+            .nodes
+            .try_mini_type_of::<T>()
+            .ok_or(NecsError::NotRegistered(type_name::<T>()))?;
+        let node_ids: Vec<NodeId> = self.storage.nodes.ids_of_type(node_type).collect();
+        // Despawning must not fail partway, so check every node beforehand.
+        for &node_id in &node_ids {
+            self.check_removable(node_id)?;
+            self.check_lifecycle(node_id)?;
+        }
+        for node_id in node_ids {
+            self.despawn_node(node_id)?;
+        }
+        self.storage.nodes.unregister::<T>();
+        self.trait_map.unregister(node_type);
+        Ok(())
+    }
     pub fn register_trait<T, Trait, F>(&mut self, to_trait_obj: F)
     where
         T: NodeRef + Node,
         Trait: NodeTrait + ?Sized + 'static,
         F: Fn(T::Instance<'static>) -> Box<Trait> + Send + Sync + 'static,
     {
+        self.ensure_registered::<T>();
         self.trait_map
             .register::<T, Trait, _>(self.storage.nodes.mini_type_of::<T>(), to_trait_obj);
     }
+    /// Allows nodes of type [T] to be cloned by
+    /// [`clone_node_recursive`](World::clone_node_recursive) as descendants,
+    /// without knowing their type.
+    ///
+    /// This is done automatically for node types without generic parameters,
+    /// and for the type of every node cloned through
+    /// [`clone_node`](World::clone_node).
+    pub fn register_clone<T: CloneNode>(&mut self) {
+        self.ensure_registered::<T>();
+        self.storage.nodes.register_clone::<T>();
+    }
+    /// Registers [T] with [`register_node`](World::register_node) if it is
+    /// not registered yet.
+    fn ensure_registered<T: NodeRef>(&mut self) {
+        if self.storage.nodes.try_mini_type_of::<T>().is_none() {
+            self.register_node::<T>();
+        }
+    }
     pub fn spawn_node<T: NodeBuilder>(&mut self, node: T) -> NodeId {
+        let node_id = self.spawn_detached(node);
+        self.enter_tree(node_id, true);
+        node_id
+    }
+    /// Spawns a root node without invoking [`Lifecycle`] callbacks.
+    fn spawn_detached<T: NodeBuilder>(&mut self, node: T) -> NodeId {
+        self.ensure_registered::<T::AsNodeRef>();
         let node_id = node.__move_to_storage(&mut self.storage);
-        self.community.insert(node_id.instance, Relations::new(None));
+        self.community
+            .insert(node_id.instance, Relations::new(None));
         node_id
     }
+    /// Spawns every node yielded by the given iterator, returning their ids in
+    /// the same order.
+    ///
+    /// This is faster than calling [`spawn_node`](World::spawn_node) in a loop,
+    /// as types are only looked up once and storage is reserved up front based
+    /// on the iterator's size hint.
+    pub fn spawn_batch<T: NodeBuilder>(
+        &mut self,
+        nodes: impl IntoIterator<Item = T>,
+    ) -> Vec<NodeId> {
+        self.ensure_registered::<T::AsNodeRef>();
+        let node_ids = T::__move_batch_to_storage(nodes, &mut self.storage);
+        self.community.reserve(node_ids.len());
+        for node_id in &node_ids {
+            self.community
+                .insert(node_id.instance, Relations::new(None));
+        }
+        for &node_id in &node_ids {
+            self.enter_tree(node_id, true);
+        }
+        node_ids
+    }
+    /// Reserves capacity for at least `additional` more nodes of type [T],
+    /// including their `#[ext]` fields.
+    pub fn reserve<T: NodeRef>(&mut self, additional: usize) {
+        self.ensure_registered::<T>();
+        T::__reserve(&mut self.storage, additional);
+        self.community.reserve(additional);
+    }
+    /// Removes the node associated with the given [`NodeId`], along with its
+    /// `#[ext]` fields and [`Relations`], freeing its key for reuse.
+    ///
+    /// Children of the node are not despawned, but become root nodes. See
+    /// [`despawn_recursive`](World::despawn_recursive) and
+    /// [`despawn_with`](World::despawn_with) for alternatives.
+    ///
+    /// # Errors
+    /// Returns [`NecsError::StaleId`] if the node does not exist, or
+    /// [`NecsError::AlreadyBorrowed`] if it, or a descendant whose
+    /// [`Lifecycle`] callbacks would be invoked, is currently borrowed, in
+    /// which case nothing is removed.
+    pub fn despawn_node(&mut self, id: NodeId) -> Result<(), NecsError> {
+        self.check_removable(id)?;
+        self.check_lifecycle(id)?;
+        self.exit_tree(id);
+        let children: Vec<NodeId> = self.children_of(id).collect();
+        self.remove_node(id)?;
+        for child in children {
+            self.enter_tree(child, false);
+        }
+        Ok(())
+    }
+    /// Ensures the given node exists and is not borrowed, so that
+    /// [`remove_node`](World::remove_node) can't fail.
+    fn check_removable(&self, id: NodeId) -> Result<(), NecsError> {
+        self.storage.nodes.check(id)?;
+        if self.is_borrowed(id) {
+            return Err(NecsError::AlreadyBorrowed(id));
+        }
+        Ok(())
+    }
+    /// Returns whether the given node is currently borrowed, without knowing
+    /// its type.
+    fn is_borrowed(&self, id: NodeId) -> bool {
+        self.storage
+            .nodes
+            .vtable_of(id.node_type)
+            .is_some_and(|vtable| (vtable.is_borrowed)(&self.storage.nodes, id))
+    }
+    /// Removes a node like [`despawn_node`](World::despawn_node) without
+    /// invoking [`Lifecycle`] callbacks.
+    fn remove_node(&mut self, id: NodeId) -> Result<(), NecsError> {
+        let remove = self
+            .storage
+            .nodes
+            .vtable_of(id.node_type)
+            .map(|vtable| vtable.remove)
+            .ok_or(NecsError::StaleId(id))?;
+        remove(&mut self.storage, id)?;
+        self.detach(id.instance);
+        self.event_handlers.remove(&id.instance);
+        if let Some(relations) = self.community.remove(&id.instance) {
+            if relations.is_flagged() {
+                self.flagged_nodes -= 1;
+            }
+            for child in relations.children() {
+                if let Some(child) = self.community.get_mut(child) {
+                    child.set_parent(None);
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Moves the node associated with the given [`NodeId`] out of the world,
+    /// returning its builder.
+    ///
+    /// The [`NodeId`] and [`Relations`] of the node stay reserved, so the node
+    /// can later be put back with [`reinsert_node`](World::reinsert_node).
+    /// Until then, it is skipped by [`get_nodes`](World::get_nodes) and
+    /// accessing it fails with [`NecsError::Taken`].
+    ///
+    /// # Errors
+    /// Fails if the node does not exist, is not of type [T], was already taken
+    /// or is currently borrowed.
+    pub fn take_node<T: NodeRef>(&mut self, id: NodeId) -> Result<T::Builder, NecsError> {
+        let node = T::__take_from_storage(&mut self.storage, id)?;
+        self.propagation_caches.invalidate(id.instance);
+        Ok(node)
+    }
+    /// Moves a node taken with [`take_node`](World::take_node) back into the
+    /// world under the same [`NodeId`].
+    ///
+    /// # Errors
+    /// Fails if the node was despawned in the meantime, is not of the builder's
+    /// type, or is not currently taken.
+    pub fn reinsert_node<T: NodeBuilder>(&mut self, id: NodeId, node: T) -> Result<(), NecsError> {
+        node.__reinsert_into_storage(&mut self.storage, id)?;
+        self.propagation_caches.invalidate(id.instance);
+        Ok(())
+    }
+    /// Spawns a copy of the node associated with the given [`NodeId`],
+    /// including its `#[ext]` fields, under the same parent.
+    ///
+    /// The copy is unnamed, as names must be unique among siblings.
+    ///
+    /// Only nodes whose fields are all [`Clone`] implement [`CloneNode`].
+    ///
+    /// # Errors
+    /// Fails if the node does not exist, is not of type [T] or is currently
+    /// borrowed.
+    pub fn clone_node<T: CloneNode>(&mut self, id: NodeId) -> Result<NodeId, NecsError> {
+        let clone_id = self.clone_detached::<T>(id)?;
+        self.enter_tree(clone_id, true);
+        Ok(clone_id)
+    }
+    /// Clones a node like [`clone_node`](World::clone_node) without invoking
+    /// [`Lifecycle`] callbacks.
+    fn clone_detached<T: CloneNode>(&mut self, id: NodeId) -> Result<NodeId, NecsError> {
+        self.register_clone::<T>();
+        let node = T::__clone_builder(&self.storage, id)?;
+        let parent = self.community.get(&id.instance).and_then(Relations::parent);
+        let clone_id = node.__move_to_storage(&mut self.storage);
+        self.adopt(parent, clone_id.instance);
+        Ok(clone_id)
+    }
+    /// Like [`clone_node`](World::clone_node), but also recursively clones
+    /// every descendant of the node, attaching the copies to the cloned node.
+    ///
+    /// Cloned descendants keep their names. Descendants are cloned without
+    /// knowing their type, which is only possible for node types implementing
+    /// [`CloneNode`] that either have no generic parameters or were passed to
+    /// [`register_clone`](World::register_clone).
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::NotCloneable`] if any descendant can't be
+    /// cloned, in which case no node is spawned.
+    pub fn clone_node_recursive<T: CloneNode>(&mut self, id: NodeId) -> Result<NodeId, NecsError> {
+        // Make sure every descendant can be cloned before spawning anything.
+        let mut stack = vec![id.instance];
+        while let Some(key) = stack.pop() {
+            let Some(relations) = self.community.get(&key) else {
+                continue;
+            };
+            for &child in relations.children() {
+                let child_id = self
+                    .storage
+                    .nodes
+                    .id_of(child)
+                    .expect("children should always be alive");
+                let vtable = self.storage.nodes.vtable_of(child_id.node_type);
+                if vtable.and_then(|vtable| vtable.clone).is_none() {
+                    return Err(NecsError::NotCloneable(child_id));
+                }
+                stack.push(child);
+            }
+        }
+
+        let clone_id = self.clone_detached::<T>(id)?;
+        let mut spawned = vec![clone_id];
+        if let Err(e) = self.clone_children(id.instance, clone_id.instance, &mut spawned) {
+            // Cloning can still fail if a descendant is borrowed, undo what was done so
+            // far.
+            for node_id in spawned.into_iter().rev() {
+                _ = self.remove_node(node_id);
+            }
+            return Err(e);
+        }
+        self.enter_tree(clone_id, true);
+        Ok(clone_id)
+    }
+    /// Clones every child of `original` as a child of `clone`, recursively.
+    fn clone_children(
+        &mut self,
+        original: ItemKey,
+        clone: ItemKey,
+        spawned: &mut Vec<NodeId>,
+    ) -> Result<(), NecsError> {
+        let children = match self.community.get(&original) {
+            Some(relations) => relations.children().to_vec(),
+            None => return Ok(()),
+        };
+        for child in children {
+            let child_id = self
+                .storage
+                .nodes
+                .id_of(child)
+                .expect("children should always be alive");
+            let clone_fn = self
+                .storage
+                .nodes
+                .vtable_of(child_id.node_type)
+                .and_then(|vtable| vtable.clone)
+                .ok_or(NecsError::NotCloneable(child_id))?;
+            let child_clone_id = clone_fn(&mut self.storage, child_id)?;
+            spawned.push(child_clone_id);
+            self.adopt(Some(clone), child_clone_id.instance);
+            let name = self.community[&child].name().map(str::to_owned);
+            self.community
+                .get_mut(&child_clone_id.instance)
+                .expect("clone should have just been adopted")
+                .set_name(name);
+            self.clone_children(child, child_clone_id.instance, spawned)?;
+        }
+        Ok(())
+    }
+    /// Creates the [`Relations`] of a freshly spawned node.
+    fn adopt(&mut self, parent: Option<ItemKey>, child: ItemKey) {
+        self.propagation_caches.invalidate(child);
+        self.community.insert(child, Relations::new(parent));
+        if let Some(parent) = parent.and_then(|parent| self.community.get_mut(&parent)) {
+            parent.push_child(child);
+        }
+    }
+    /// Changes the concrete type of a node from [From] to [To] by converting
+    /// its builder with `f`, returning its new [`NodeId`].
+    ///
+    /// The node keeps its [`ItemKey`], and with it its [`Relations`]. Accessing
+    /// the node through its old [`NodeId`] fails with
+    /// [`NecsError::TypeChanged`], which holds the new [`NodeId`].
+    ///
+    /// # Errors
+    /// Fails if the node does not exist, is not of type [From], or is currently
+    /// borrowed, in which case the node is left untouched.
+    pub fn morph_node<From, To>(
+        &mut self,
+        id: NodeId,
+        f: impl FnOnce(From::Builder) -> To::Builder,
+    ) -> Result<NodeId, NecsError>
+    where
+        From: NodeRef,
+        To: NodeRef,
+    {
+        self.ensure_registered::<To>();
+        let node = f(self.take_node::<From>(id)?);
+        let new_id = self.storage.nodes.retype::<From, To>(id)?;
+        node.__reinsert_into_storage(&mut self.storage, new_id)?;
+        Ok(new_id)
+    }
+    /// Returns whether the given [`NodeId`] refers to a node that currently
+    /// exists.
+    ///
+    /// Ids of despawned nodes are never considered contained, even once their
+    /// slot is reused by another node. Nodes taken out of the world with
+    /// [`take_node`](World::take_node) are still contained.
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.storage.nodes.contains(id)
+    }
+    /// Gets a node of concrete type [T].
+    ///
+    /// # Panics
+    /// See [`try_get_node`](World::try_get_node) for the conditions under
+    /// which this panics.
     pub fn get_node<T: NodeRef>(&self, id: NodeId) -> T::Instance<'_> {
         // The safety of this entirely depends on everything else not having issues.
         let (recipe_tuple, borrow_dropper) = self.storage.nodes.get_element::<T>(id);
         unsafe { T::__build_from_storage(recipe_tuple, borrow_dropper, &self.storage, id) }
     }
+    /// Gets a node of concrete type [T].
+    ///
+    /// # Errors
+    /// Fails if [T] is not registered, the node does not exist or is of another
+    /// type, or if the node is already borrowed.
+    pub fn try_get_node<T: NodeRef>(&self, id: NodeId) -> Result<T::Instance<'_>, NecsError> {
+        let (recipe_tuple, borrow_dropper) = self.storage.nodes.try_get_element::<T>(id)?;
+        Ok(unsafe { T::__build_from_storage(recipe_tuple, borrow_dropper, &self.storage, id) })
+    }
+    /// Gets every active node of concrete type [T], see
+    /// [`is_active`](World::is_active).
+    ///
+    /// Every returned node stays borrowed until the [`Vec`] is dropped, see
+    /// [`iter_nodes`](World::iter_nodes) for a lazy alternative.
     pub fn get_nodes<T: NodeRef>(&self) -> Vec<T::Instance<'_>> {
-        let ids = self.get_node_ids::<T>();
+        if self.all_active() {
+            return self.get_all_nodes::<T>();
+        }
+        self.iter_nodes::<T>().collect()
+    }
+    /// Gets every node of concrete type [T], including inactive ones.
+    pub fn get_all_nodes<T: NodeRef>(&self) -> Vec<T::Instance<'_>> {
+        let ids = self.get_all_node_ids::<T>();
 
         let mut nodes = Vec::with_capacity(ids.len());
 
@@ -86,9 +477,154 @@ impl World {
 
         nodes
     }
+    /// Lazily iterates over every active node of concrete type [T], see
+    /// [`is_active`](World::is_active).
+    ///
+    /// Nodes are only borrowed once they are yielded, and nothing is
+    /// allocated.
+    ///
+    /// # Panics
+    /// Panics when reaching a node that is already borrowed.
+    pub fn iter_nodes<T: NodeRef>(&self) -> impl Iterator<Item = T::Instance<'_>> {
+        let all_active = self.all_active();
+        self.storage
+            .nodes
+            .iter_cells::<T>()
+            .filter(move |&(id, _)| all_active || self.is_active(id))
+            .map(|(id, node_cell)| self.build_node::<T>(id, node_cell))
+    }
+    /// Like [`iter_nodes`](World::iter_nodes), but also yields inactive
+    /// nodes.
+    pub fn iter_all_nodes<T: NodeRef>(&self) -> impl ExactSizeIterator<Item = T::Instance<'_>> {
+        self.storage
+            .nodes
+            .iter_cells::<T>()
+            .map(|(id, node_cell)| self.build_node::<T>(id, node_cell))
+    }
+    /// Calls `f` on every active node of concrete type [T], borrowing one node
+    /// at a time, see [`iter_nodes`](World::iter_nodes).
+    pub fn for_each_node<T: NodeRef>(&self, f: impl FnMut(T::Instance<'_>)) {
+        self.iter_nodes::<T>().for_each(f);
+    }
+    fn build_node<'world, T: NodeRef>(
+        &'world self,
+        id: NodeId,
+        node_cell: &'world RecipeTupleCell<T::RecipeTuple>,
+    ) -> T::Instance<'world> {
+        let (recipe_tuple, borrow_dropper) = node_cell
+            .try_borrow()
+            .unwrap_or_else(|| panic!("{}", NecsError::AlreadyBorrowed(id)));
+        unsafe { T::__build_from_storage(recipe_tuple, borrow_dropper, &self.storage, id) }
+    }
+    /// Returns the ids of every active node of concrete type [T], see
+    /// [`is_active`](World::is_active).
     pub fn get_node_ids<T: NodeRef>(&self) -> impl ExactSizeIterator<Item = NodeId> {
+        self.active_ids(self.storage.nodes.get_ids::<T>())
+    }
+    /// Returns the ids of every node of concrete type [T], including inactive
+    /// ones.
+    pub fn get_all_node_ids<T: NodeRef>(&self) -> impl ExactSizeIterator<Item = NodeId> {
         self.storage.nodes.get_ids::<T>()
     }
+    /// Iterates over every `#[ext]` field of type [T] of active nodes, along
+    /// with the id of the node it belongs to, regardless of the node's type.
+    ///
+    /// Each node stays borrowed while its field is, so other nodes can still be
+    /// accessed meanwhile.
+    ///
+    /// # Panics
+    /// Panics when reaching a node that is currently borrowed, for example
+    /// through [`get_node_resilient`](World::get_node_resilient).
+    pub fn components<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl Iterator<Item = (NodeId, ComponentMut<'_, T>)> {
+        self.iter_components::<T>(true)
+    }
+    /// Like [`components`](World::components), but also yields the fields of
+    /// inactive nodes.
+    pub fn all_components<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl Iterator<Item = (NodeId, ComponentMut<'_, T>)> {
+        self.iter_components::<T>(false)
+    }
+    /// Iterates over every component of type [T], skipping those of inactive
+    /// nodes if `active_only` is set.
+    fn iter_components<T: Send + Sync + 'static>(
+        &self,
+        active_only: bool,
+    ) -> impl Iterator<Item = (NodeId, ComponentMut<'_, T>)> {
+        let check_active = active_only && !self.all_active();
+        // If T is not registered there are no components, so the placeholder is never
+        // used.
+        let component_type = self
+            .storage
+            .components
+            .try_mini_type_of::<T>()
+            .unwrap_or(MiniTypeId::MAX);
+        self.storage.components.keys::<T>().filter_map(move |&key| {
+            let id = self
+                .storage
+                .nodes
+                .id_of(key)
+                .expect("components should always belong to a node");
+            if check_active && !self.is_active(id) {
+                return None;
+            }
+            let borrow_dropper = self
+                .storage
+                .nodes
+                .try_borrow(id)
+                .unwrap_or_else(|e| panic!("{}", e));
+            // Safety: component_type is that of T, and the node's borrow guards its
+            // #[ext] fields until the BorrowDropper is dropped.
+            let component = unsafe {
+                self.storage
+                    .components
+                    .try_get_element_unchecked(&ComponentId::<T>::new(component_type, key))
+            };
+            Some((
+                id,
+                ComponentMut::new(component.expect("the key was just yielded"), borrow_dropper),
+            ))
+        })
+    }
+    /// Panics if the given node is currently borrowed, in which case its
+    /// components must not be accessed.
+    fn assert_not_borrowed(&self, id: NodeId) {
+        if self.is_borrowed(id) {
+            panic!("{}", NecsError::AlreadyBorrowed(id));
+        }
+    }
+    /// Returns a [`Commands`] buffer recording changes to be applied later by
+    /// [`apply_commands`](World::apply_commands).
+    ///
+    /// Unlike methods such as [`spawn_node`](World::spawn_node), this only
+    /// requires a shared reference, so it can be used while nodes are borrowed.
+    pub fn commands(&self) -> Commands<'_> {
+        Commands::new(&self.commands)
+    }
+    /// Applies every command recorded through [`commands`](World::commands) in
+    /// the order they were recorded, including commands recorded while
+    /// applying.
+    ///
+    /// # Errors
+    /// A failing command does not prevent later commands from being applied,
+    /// the first error encountered is returned once all commands are applied.
+    pub fn apply_commands(&mut self) -> Result<(), NecsError> {
+        let mut result = Ok(());
+        loop {
+            let commands = self.commands.take();
+            if commands.is_empty() {
+                return result;
+            }
+            for command in commands {
+                let command_result = command(self);
+                if result.is_ok() {
+                    result = command_result;
+                }
+            }
+        }
+    }
 
     /// Gets a node of type [T].
     ///
@@ -97,11 +633,44 @@ impl World {
     /// rather than the concrete type of the node.
     ///
     /// # Panics
-    /// The node associated with the given [`NodeId`] must be of type [T].
-    // TODO: Change panic doc ^
+    /// See [`try_get_node_resilient`](World::try_get_node_resilient) for the
+    /// conditions under which this panics.
     pub fn get_node_resilient<T: 'static + NodeTrait + ?Sized>(&self, id: NodeId) -> Box<T> {
         self.trait_map.get_node::<T>(&self.storage, id)
     }
+
+    /// Gets every active node whose type was registered for [T], see
+    /// [`iter_nodes_resilient`](World::iter_nodes_resilient).
+    ///
+    /// Every returned node stays borrowed until the [`Vec`] is dropped.
+    pub fn get_nodes_resilient<T: 'static + NodeTrait + ?Sized>(&self) -> Vec<(NodeId, Box<T>)> {
+        self.iter_nodes_resilient::<T>().collect()
+    }
+
+    /// Lazily iterates over every active node whose type was registered for
+    /// [T], whatever its concrete type, along with its id. Taken nodes are
+    /// skipped.
+    ///
+    /// # Panics
+    /// Panics when reaching a node that is already borrowed.
+    pub fn iter_nodes_resilient<T: 'static + NodeTrait + ?Sized>(
+        &self,
+    ) -> impl Iterator<Item = (NodeId, Box<T>)> {
+        self.query_nodes().nodes_resilient::<T>()
+    }
+
+    /// Gets a node of type [T], where [T] is a registered subtrait of
+    /// [NodeTrait].
+    ///
+    /// # Errors
+    /// Fails if the node does not exist, if its type was not registered for
+    /// [T], or if the node is already borrowed.
+    pub fn try_get_node_resilient<T: 'static + NodeTrait + ?Sized>(
+        &self,
+        id: NodeId,
+    ) -> Result<Box<T>, NecsError> {
+        self.trait_map.try_get_node::<T>(&self.storage, id)
+    }
 }
 
 impl Default for World {
@@ -110,6 +679,11 @@ impl Default for World {
             storage: Storage::new(),
             trait_map: TraitMap::new(),
             community: HashMap::default(),
+            commands: CommandQueue::default(),
+            propagation_caches: PropagationCaches::default(),
+            event_handlers: HashMap::default(),
+            paused: false,
+            flagged_nodes: 0,
         }
     }
 }
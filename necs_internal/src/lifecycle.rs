@@ -0,0 +1,117 @@
+use crate::{NecsError, NodeId, NodeTrait, World};
+
+/// Callbacks invoked when a node's place in the hierarchy changes.
+///
+/// Implement this on the node instance type and register it for
+/// `dyn Lifecycle` with [`World::register_trait`]. Every callback does nothing
+/// by default.
+///
+/// A node enters the tree when it is spawned, and leaves it when it is
+/// despawned. Moving a node to another parent, or making it a root node, makes
+/// its whole subtree leave the tree and enter it again.
+///
+/// Structural changes fail with [`NecsError::AlreadyBorrowed`] if a node
+/// whose callbacks would be invoked is currently borrowed, in which case
+/// nothing changes.
+pub trait Lifecycle: NodeTrait {
+    /// Called when the node enters the tree, before it is called on the
+    /// node's descendants.
+    fn on_enter_tree(&mut self, world: &World, id: NodeId) {
+        _ = (world, id);
+    }
+
+    /// Called once after the node was spawned and entered the tree, after it
+    /// was called on the node's descendants.
+    fn on_ready(&mut self, world: &World, id: NodeId) {
+        _ = (world, id);
+    }
+
+    /// Called when the node is about to leave the tree, after it was called
+    /// on the node's descendants. The whole subtree is still intact.
+    fn on_exit_tree(&mut self, world: &World, id: NodeId) {
+        _ = (world, id);
+    }
+}
+
+impl World {
+    /// Ensures no node of the subtree of the given node whose [`Lifecycle`]
+    /// callbacks would be invoked is currently borrowed.
+    pub(crate) fn check_lifecycle(&self, id: NodeId) -> Result<(), NecsError> {
+        if !self.trait_map.is_registered::<dyn Lifecycle>() {
+            return Ok(());
+        }
+        for node_id in [id].into_iter().chain(self.descendants(id)) {
+            if self
+                .trait_map
+                .implements::<dyn Lifecycle>(node_id.node_type)
+                && self.is_borrowed(node_id)
+            {
+                return Err(NecsError::AlreadyBorrowed(node_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Invokes [`Lifecycle::on_enter_tree`] on the subtree of the given node,
+    /// followed by [`Lifecycle::on_ready`] if it was just `spawned`.
+    pub(crate) fn enter_tree(&self, id: NodeId, spawned: bool) {
+        if !self.trait_map.is_registered::<dyn Lifecycle>() {
+            return;
+        }
+        self.notify(id, <dyn Lifecycle>::on_enter_tree);
+        for descendant in self.descendants(id) {
+            self.notify(descendant, <dyn Lifecycle>::on_enter_tree);
+        }
+        if spawned {
+            for node_id in self.post_order(id) {
+                self.notify(node_id, <dyn Lifecycle>::on_ready);
+            }
+        }
+    }
+
+    /// Invokes [`Lifecycle::on_exit_tree`] on the subtree of the given node.
+    pub(crate) fn exit_tree(&self, id: NodeId) {
+        if !self.trait_map.is_registered::<dyn Lifecycle>() {
+            return;
+        }
+        for node_id in self.post_order(id) {
+            self.notify(node_id, <dyn Lifecycle>::on_exit_tree);
+        }
+    }
+
+    fn notify(&self, id: NodeId, callback: fn(&mut (dyn Lifecycle + 'static), &World, NodeId)) {
+        match self.try_get_node_resilient::<dyn Lifecycle>(id) {
+            Ok(mut node) => callback(&mut *node, self, id),
+            // Nodes were checked with check_lifecycle, so they can only be borrowed if a
+            // callback kept one borrowed, in which case it is skipped.
+            Err(
+                NecsError::TraitNotImplemented { .. }
+                | NecsError::Taken(_)
+                | NecsError::AlreadyBorrowed(_),
+            ) => {}
+            Err(e) => unreachable!("nodes in the tree should exist: {}", e),
+        }
+    }
+
+    /// Returns the subtree of the given node with children before their
+    /// parents, keeping the order of siblings.
+    fn post_order(&self, id: NodeId) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        // Nodes left to visit, along with whether their children were already pushed.
+        let mut stack = vec![(id.instance, false)];
+        while let Some((key, expanded)) = stack.pop() {
+            if expanded {
+                order.push(self.id_of_relative(key));
+                continue;
+            }
+            stack.push((key, true));
+            stack.extend(
+                self.child_keys(key)
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, false)),
+            );
+        }
+        order
+    }
+}
@@ -1,13 +1,15 @@
 use crate::BorrowDropper;
 use crate::ItemKey;
+use crate::NecsError;
 use crate::Storage;
 use crate::storage::MiniTypeId;
 use std::any::{Any, type_name};
+use std::marker::PhantomData;
 
 /// Used with [`get_node`](crate::World::get_node) or
 /// [`get_node_resilient`](crate::World::get_node_resilient) to retrieve nodes
 /// stored by [`World`](crate::World).
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct NodeId {
     // TODO: is it safe having this buildable? If two different NodeTypes are given the same key,
     // there's chaos since multiple nodes can have the same component.
@@ -20,10 +22,21 @@ pub trait Field: Any {}
 impl<T: 'static> Field for T {}
 
 impl dyn Field {
+    /// Downcasts this field to [`T`].
+    ///
+    /// # Panics
+    /// The field must be of type [`T`], see [`try_to`](Field::try_to)
+    /// for a non-panicking alternative.
     pub fn to<T: 'static>(&mut self) -> &mut T {
+        self.try_to::<T>().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Downcasts this field to [`T`], failing with
+    /// [`NecsError::WrongFieldType`] if it is of another type.
+    pub fn try_to<T: 'static>(&mut self) -> Result<&mut T, NecsError> {
         (self as &mut dyn Any)
             .downcast_mut::<T>()
-            .unwrap_or_else(|| panic!("invalid downcast to {}", type_name::<T>()))
+            .ok_or(NecsError::WrongFieldType(type_name::<T>()))
     }
 }
 
@@ -36,6 +49,17 @@ pub trait NodeBuilder {
 
     /// Moves all fields to a given [`Storage`].
     fn __move_to_storage(self, storage: &mut Storage) -> NodeId;
+
+    /// Moves all fields back to a given [`Storage`] under the [`NodeId`] they
+    /// were taken from with [`NodeRef::__take_from_storage`].
+    fn __reinsert_into_storage(self, storage: &mut Storage, id: NodeId) -> Result<(), NecsError>;
+
+    /// Moves all fields of every given node to a given [`Storage`], looking up
+    /// types and reserving capacity only once.
+    fn __move_batch_to_storage<I>(nodes: I, storage: &mut Storage) -> Vec<NodeId>
+    where
+        I: IntoIterator<Item = Self>,
+        Self: Sized;
 }
 
 /// Do **not** implement this trait.
@@ -43,6 +67,9 @@ pub trait NodeBuilder {
 pub trait NodeRef: 'static + NodeTrait {
     type Instance<'node>: Node;
     type RecipeTuple: Send + Sync;
+    /// The implementation of [`NodeBuilder`] associated with this
+    /// implementation of [`NodeRef`].
+    type Builder: NodeBuilder<AsNodeRef = Self>;
 
     /// Assembles a [`NodeRef`] from fields stored in the given [`Storage`].
     /// # Safety
@@ -58,6 +85,62 @@ pub trait NodeRef: 'static + NodeTrait {
     /// Registers this node to node storage and all fields with the `#[ext]`
     /// attribute to component storage.
     fn __register_node(storage: &mut Storage);
+
+    /// Reserves capacity for at least `additional` more nodes of this type in
+    /// node storage and for their `#[ext]` fields in component storage.
+    fn __reserve(storage: &mut Storage, additional: usize);
+
+    /// Removes the node associated with the given [`NodeId`] from node storage
+    /// along with all of its `#[ext]` fields from component storage.
+    fn __remove_from_storage(storage: &mut Storage, id: NodeId) -> Result<(), NecsError>;
+
+    /// Moves the node associated with the given [`NodeId`] out of storage into
+    /// its builder, keeping the [`NodeId`] reserved.
+    fn __take_from_storage(storage: &mut Storage, id: NodeId) -> Result<Self::Builder, NecsError>;
+}
+
+/// Do **not** implement this trait.
+/// This trait is implemented by the corresponding proc macro crate for every
+/// node whose fields are all [`Clone`], enabling
+/// [`clone_node`](crate::World::clone_node).
+pub trait CloneNode: NodeRef {
+    /// Clones the fields of the node associated with the given [`NodeId`] into
+    /// a new builder.
+    fn __clone_builder(storage: &Storage, id: NodeId) -> Result<Self::Builder, NecsError>;
+}
+
+/// Used by the #[node] macro to register [`CloneNode`] glue only for nodes
+/// implementing it, see [`ViaCloneNode`] and [`ViaNoCloneNode`].
+#[doc(hidden)]
+pub struct CloneProbe<T>(PhantomData<T>);
+
+impl<T> CloneProbe<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Selected through autoref when `T` implements [`CloneNode`].
+#[doc(hidden)]
+pub trait ViaCloneNode {
+    fn __register_clone(&self, storage: &mut Storage);
+}
+
+impl<T: CloneNode> ViaCloneNode for &CloneProbe<T> {
+    fn __register_clone(&self, storage: &mut Storage) {
+        storage.nodes.register_clone::<T>();
+    }
+}
+
+/// Selected through autoref when `T` does not implement [`CloneNode`].
+#[doc(hidden)]
+pub trait ViaNoCloneNode {
+    fn __register_clone(&self, storage: &mut Storage);
+}
+
+impl<T> ViaNoCloneNode for CloneProbe<T> {
+    fn __register_clone(&self, _storage: &mut Storage) {}
 }
 
 /// Require this on any trait that should be compatible with
@@ -0,0 +1,115 @@
+use crate::{ItemKey, NecsError, NodeId, World};
+
+impl World {
+    /// Gives the node a name, which must be unique among its siblings.
+    ///
+    /// Names are used to look nodes up with
+    /// [`get_node_by_path`](World::get_node_by_path). Root nodes may share
+    /// names, as they have no siblings.
+    ///
+    /// # Errors
+    /// Fails if the node does not exist, with [`NecsError::InvalidName`] if
+    /// the name is empty, `.`, `..` or contains a `/`, or with
+    /// [`NecsError::NameTaken`] if a sibling already has the name.
+    pub fn set_name(&mut self, id: NodeId, name: impl Into<String>) -> Result<(), NecsError> {
+        self.storage.nodes.check(id)?;
+        let name = name.into();
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(NecsError::InvalidName);
+        }
+        if let Some(parent) = self.parent_of(id) {
+            let sibling = self.child_named(parent.instance, &name);
+            if let Some(sibling) = sibling.filter(|&sibling| sibling != id) {
+                return Err(NecsError::NameTaken(sibling));
+            }
+        }
+        self.relations_mut(id.instance).set_name(Some(name));
+        Ok(())
+    }
+
+    /// Removes the name of the node, if it has one.
+    ///
+    /// # Errors
+    /// Fails if the node does not exist.
+    pub fn remove_name(&mut self, id: NodeId) -> Result<(), NecsError> {
+        self.storage.nodes.check(id)?;
+        self.relations_mut(id.instance).set_name(None);
+        Ok(())
+    }
+
+    /// Returns the name of the node, or [`None`] if it is unnamed or does not
+    /// exist.
+    pub fn name_of(&self, id: NodeId) -> Option<&str> {
+        if !self.contains(id) {
+            return None;
+        }
+        self.community.get(&id.instance)?.name()
+    }
+
+    /// Looks up a node by a `/` separated path of names, starting from `from`.
+    ///
+    /// `..` segments go to the parent, while `.` and empty segments stay on the
+    /// current node, so an empty path returns `from` itself.
+    /// ```
+    /// # use necs::{World, node};
+    /// # #[node]
+    /// # struct Empty;
+    /// let mut world = World::new();
+    /// let level = world.spawn_node(EmptyBuilder);
+    /// let player = world.spawn_child(level, EmptyBuilder).unwrap();
+    /// let weapon = world.spawn_child(player, EmptyBuilder).unwrap();
+    /// world.set_name(player, "player").unwrap();
+    /// world.set_name(weapon, "weapon").unwrap();
+    ///
+    /// assert_eq!(world.get_node_by_path(level, "player/weapon"), Some(weapon));
+    /// assert_eq!(world.get_node_by_path(weapon, "../.."), Some(level));
+    /// assert_eq!(world.path_of(weapon).as_deref(), Some("player/weapon"));
+    /// ```
+    ///
+    /// Returns [`None`] if `from` does not exist or no node is found.
+    pub fn get_node_by_path(&self, from: NodeId, path: &str) -> Option<NodeId> {
+        if !self.contains(from) {
+            return None;
+        }
+        path.split('/')
+            .try_fold(from, |current, segment| match segment {
+                "" | "." => Some(current),
+                ".." => self.parent_of(current),
+                name => self.child_named(current.instance, name),
+            })
+    }
+
+    /// Returns the path leading from the root of the node's tree to the node,
+    /// such that passing it to [`get_node_by_path`](World::get_node_by_path)
+    /// along with the root returns the node. The path of a root node is empty.
+    ///
+    /// Returns [`None`] if the node does not exist or it or any of its
+    /// ancestors other than the root is unnamed.
+    pub fn path_of(&self, id: NodeId) -> Option<String> {
+        if !self.contains(id) {
+            return None;
+        }
+        let mut names = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent_of(current) {
+            names.push(self.name_of(current)?);
+            current = parent;
+        }
+        names.reverse();
+        Some(names.join("/"))
+    }
+
+    /// Returns the child of `parent` with the given name, if any.
+    pub(crate) fn child_named(&self, parent: ItemKey, name: &str) -> Option<NodeId> {
+        self.community
+            .get(&parent)?
+            .children()
+            .iter()
+            .find(|child| {
+                self.community
+                    .get(child)
+                    .is_some_and(|relations| relations.name() == Some(name))
+            })
+            .and_then(|&child| self.storage.nodes.id_of(child))
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{ComponentId, ItemKey, NecsError, Relations, World};
+use rustc_hash::FxHashMap as HashMap;
+use std::any::{Any, TypeId};
+use std::fmt::{Debug, Formatter};
+
+/// What [`World::propagate`] remembers about a node between passes.
+struct PropagationEntry<Local> {
+    // The parent whose Global was combined with the node's Local, if any.
+    parent: Option<ItemKey>,
+    local: Local,
+    // The last pass during which the node was visited.
+    pass: u64,
+    // Whether the node was recomputed during that pass.
+    recomputed: bool,
+}
+
+/// The state kept by [`World::propagate_changed`] for a pair of `Local` and
+/// `Global` types, reused across passes.
+struct PropagationCache<Local> {
+    pass: u64,
+    entries: HashMap<ItemKey, PropagationEntry<Local>>,
+    // Scratch buffers, kept to avoid reallocating them every pass.
+    participants: Vec<ItemKey>,
+    chain: Vec<ItemKey>,
+}
+
+/// A [`PropagationCache`] whose `Local` type is erased.
+trait ErasedPropagationCache: Any + Send + Sync {
+    /// Forgets what was seen of the given node, so it is recomputed.
+    fn invalidate(&mut self, key: ItemKey);
+}
+
+impl<Local: Send + Sync + 'static> ErasedPropagationCache for PropagationCache<Local> {
+    fn invalidate(&mut self, key: ItemKey) {
+        self.entries.remove(&key);
+    }
+}
+
+/// The [`PropagationCache`] of every pair of `Local` and `Global` types that
+/// was propagated.
+#[derive(Default)]
+pub(crate) struct PropagationCaches(HashMap<TypeId, Box<dyn ErasedPropagationCache>>);
+
+impl Debug for PropagationCaches {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl PropagationCaches {
+    /// Forgets what was seen of the given node by every cache, for when its
+    /// fields or position in the hierarchy may have changed behind their back.
+    pub(crate) fn invalidate(&mut self, key: ItemKey) {
+        for cache in self.0.values_mut() {
+            cache.invalidate(key);
+        }
+    }
+}
+
+impl<Local> Default for PropagationCache<Local> {
+    fn default() -> Self {
+        Self {
+            pass: 0,
+            entries: HashMap::default(),
+            participants: Vec::new(),
+            chain: Vec::new(),
+        }
+    }
+}
+
+impl World {
+    /// Writes the `Global` `#[ext]` field of every node from its own `Local`
+    /// field and the `Global` field of its parent, parents first.
+    ///
+    /// `combine` receives [`None`] for nodes whose parent lacks either field.
+    /// Nodes lacking either field are skipped, so their children are treated
+    /// like roots.
+    ///
+    /// Every node having both fields is recomputed, see
+    /// [`propagate_changed`](World::propagate_changed) to skip unchanged
+    /// subtrees instead.
+    ///
+    /// # Panics
+    /// `Local` and `Global` must be different types.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::AlreadyBorrowed`] if a node having both fields
+    /// is currently borrowed, in which case nothing is written.
+    pub fn propagate<Local, Global, F>(&mut self, combine: F) -> Result<(), NecsError>
+    where
+        Local: Clone + PartialEq + Send + Sync + 'static,
+        Global: Send + Sync + 'static,
+        F: Fn(Option<&Global>, &Local) -> Global,
+    {
+        self.propagate_with(combine, false)
+    }
+
+    /// Like [`propagate`](World::propagate), but only recomputes nodes whose
+    /// `Local` field or parent changed since the last pass, or whose parent was
+    /// recomputed, so unchanged subtrees are skipped.
+    ///
+    /// Nodes that were taken, reinserted, morphed, cloned or reparented since
+    /// are recomputed as well. Anything else changing a `Global` field goes
+    /// unnoticed though, so `combine` must be pure and the same on every call
+    /// for a given pair of types, and `Global` fields should only be written
+    /// by propagating. Otherwise, call [`propagate`](World::propagate) once to
+    /// get back in sync.
+    ///
+    /// # Panics
+    /// `Local` and `Global` must be different types.
+    ///
+    /// # Errors
+    /// Fails with [`NecsError::AlreadyBorrowed`] if a node having both fields
+    /// is currently borrowed, in which case nothing is written.
+    pub fn propagate_changed<Local, Global, F>(&mut self, combine: F) -> Result<(), NecsError>
+    where
+        Local: Clone + PartialEq + Send + Sync + 'static,
+        Global: Send + Sync + 'static,
+        F: Fn(Option<&Global>, &Local) -> Global,
+    {
+        self.propagate_with(combine, true)
+    }
+
+    /// Implements [`propagate`](World::propagate) and
+    /// [`propagate_changed`](World::propagate_changed). Both keep the cache up
+    /// to date, so that they can be mixed.
+    fn propagate_with<Local, Global, F>(
+        &mut self,
+        combine: F,
+        skip_unchanged: bool,
+    ) -> Result<(), NecsError>
+    where
+        Local: Clone + PartialEq + Send + Sync + 'static,
+        Global: Send + Sync + 'static,
+        F: Fn(Option<&Global>, &Local) -> Global,
+    {
+        assert_ne!(
+            TypeId::of::<Local>(),
+            TypeId::of::<Global>(),
+            "Local and Global must be different types"
+        );
+        let components = &self.storage.components;
+        let nodes = &self.storage.nodes;
+        let community = &self.community;
+        let (Some(local_type), Some(global_type)) = (
+            components.try_mini_type_of::<Local>(),
+            components.try_mini_type_of::<Global>(),
+        ) else {
+            return Ok(());
+        };
+        let cache: &mut dyn Any = &mut **self
+            .propagation_caches
+            .0
+            .entry(TypeId::of::<(Local, Global)>())
+            .or_insert_with(|| Box::new(PropagationCache::<Local>::default()));
+        let cache = cache
+            .downcast_mut::<PropagationCache<Local>>()
+            .expect("cache should be of the right type");
+
+        // Collect the nodes having both fields from the smaller column, making sure
+        // none of them is borrowed before writing anything.
+        let participates =
+            |key: ItemKey| components.contains::<Local>(key) && components.contains::<Global>(key);
+        cache.participants.clear();
+        if components.keys::<Local>().len() <= components.keys::<Global>().len() {
+            let keys = components.keys::<Local>().copied();
+            cache
+                .participants
+                .extend(keys.filter(|&key| components.contains::<Global>(key)));
+        } else {
+            let keys = components.keys::<Global>().copied();
+            cache
+                .participants
+                .extend(keys.filter(|&key| components.contains::<Local>(key)));
+        }
+        for &key in &cache.participants {
+            let id = nodes
+                .id_of(key)
+                .expect("components should always belong to a node");
+            let is_borrowed = nodes.vtable_of(id.node_type);
+            if is_borrowed.is_some_and(|vtable| (vtable.is_borrowed)(nodes, id)) {
+                return Err(NecsError::AlreadyBorrowed(id));
+            }
+        }
+
+        cache.pass += 1;
+        let pass = cache.pass;
+        let parent_of = |key: ItemKey| {
+            community
+                .get(&key)
+                .and_then(Relations::parent)
+                .filter(|&parent| participates(parent))
+        };
+        for i in 0..cache.participants.len() {
+            let key = cache.participants[i];
+            if cache
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.pass == pass)
+            {
+                continue;
+            }
+            // Parents must be visited before their children, so first collect the
+            // ancestors that were not visited yet during this pass.
+            cache.chain.clear();
+            let mut current = Some(key);
+            while let Some(key) = current {
+                cache.chain.push(key);
+                current = parent_of(key).filter(|parent| {
+                    cache
+                        .entries
+                        .get(parent)
+                        .is_none_or(|entry| entry.pass != pass)
+                });
+            }
+
+            while let Some(key) = cache.chain.pop() {
+                let parent = parent_of(key);
+                let parent_recomputed =
+                    parent.is_some_and(|parent| cache.entries[&parent].recomputed);
+                // Safety: the component types match the ids, nothing else can reference
+                // components as we hold &mut self and no node having both fields is
+                // borrowed, and a node's Global is never accessed while that of its
+                // parent is.
+                let local = unsafe {
+                    &*components.get_element_unchecked(&ComponentId::<Local>::new(local_type, key))
+                };
+                let recomputed = match cache.entries.get_mut(&key) {
+                    Some(entry) => {
+                        let local_changed = entry.local != *local;
+                        if local_changed {
+                            entry.local.clone_from(local);
+                        }
+                        let recomputed = !skip_unchanged
+                            || parent_recomputed
+                            || local_changed
+                            || entry.parent != parent;
+                        entry.parent = parent;
+                        entry.pass = pass;
+                        entry.recomputed = recomputed;
+                        recomputed
+                    }
+                    None => {
+                        cache.entries.insert(
+                            key,
+                            PropagationEntry {
+                                parent,
+                                local: local.clone(),
+                                pass,
+                                recomputed: true,
+                            },
+                        );
+                        true
+                    }
+                };
+                if recomputed {
+                    let parent_global = parent.map(|parent| unsafe {
+                        &*components
+                            .get_element_unchecked(&ComponentId::<Global>::new(global_type, parent))
+                    });
+                    let global = unsafe {
+                        components
+                            .get_element_unchecked(&ComponentId::<Global>::new(global_type, key))
+                    };
+                    *global = combine(parent_global, local);
+                }
+            }
+        }
+        // Forget nodes that lost either field or were despawned.
+        if cache.entries.len() > cache.participants.len() {
+            cache.entries.retain(|_, entry| entry.pass == pass);
+        }
+        Ok(())
+    }
+}
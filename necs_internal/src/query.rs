@@ -0,0 +1,444 @@
+use crate::storage::{ComponentStorage, ItemKey, MiniTypeId};
+use crate::{ComponentId, NecsError, NodeId, NodeRef, NodeTrait, World};
+use std::any::{TypeId, type_name};
+use std::fmt::{Debug, Formatter};
+
+/// A single component access of a [`Query`], either `&T` or `&mut T`.
+pub trait Fetch {
+    /// The accessed component type.
+    type Component: Send + Sync + 'static;
+    /// The reference yielded for each node.
+    type Item<'world>;
+    /// Whether the component is accessed mutably.
+    const MUTABLE: bool;
+
+    /// Gets the component of the given node, if it has one.
+    ///
+    /// # Safety
+    /// `component_type` must be that of [`Fetch::Component`], and no reference
+    /// conflicting with the returned one may exist while it is alive.
+    #[doc(hidden)]
+    unsafe fn fetch(
+        components: &ComponentStorage,
+        component_type: MiniTypeId,
+        key: ItemKey,
+    ) -> Option<Self::Item<'_>>;
+}
+
+impl<T: Send + Sync + 'static> Fetch for &T {
+    type Component = T;
+    type Item<'world> = &'world T;
+    const MUTABLE: bool = false;
+
+    unsafe fn fetch(
+        components: &ComponentStorage,
+        component_type: MiniTypeId,
+        key: ItemKey,
+    ) -> Option<&T> {
+        // Safety: upheld by the caller.
+        unsafe {
+            components
+                .try_get_element_unchecked(&ComponentId::<T>::new(component_type, key))
+                .map(|component| &*component)
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> Fetch for &mut T {
+    type Component = T;
+    type Item<'world> = &'world mut T;
+    const MUTABLE: bool = true;
+
+    unsafe fn fetch(
+        components: &ComponentStorage,
+        component_type: MiniTypeId,
+        key: ItemKey,
+    ) -> Option<&mut T> {
+        // Safety: upheld by the caller.
+        unsafe { components.try_get_element_unchecked(&ComponentId::<T>::new(component_type, key)) }
+    }
+}
+
+/// A tuple of [`Fetch`] accesses iterated by [`World::query`], such as
+/// `(&mut Position, &Velocity)`.
+pub trait Query {
+    /// The tuple of references yielded for each node.
+    type Item<'world>;
+    /// The resolved component types.
+    #[doc(hidden)]
+    type State: Copy;
+
+    /// The accessed component types, their names and whether they are
+    /// accessed mutably.
+    #[doc(hidden)]
+    fn accesses() -> Vec<(TypeId, &'static str, bool)>;
+
+    /// Resolves the component types, or returns [`None`] if one of them was
+    /// never registered, in which case no node matches.
+    #[doc(hidden)]
+    fn state(components: &ComponentStorage) -> Option<Self::State>;
+
+    /// Iterates over the keys of the smallest accessed column.
+    #[doc(hidden)]
+    fn driver(components: &ComponentStorage) -> Box<dyn Iterator<Item = ItemKey> + '_>;
+
+    /// Gets every accessed component of the given node, or [`None`] if it
+    /// lacks one of them.
+    ///
+    /// # Safety
+    /// `state` must come from [`Query::state`], and no reference conflicting
+    /// with the returned ones may exist while they are alive.
+    #[doc(hidden)]
+    unsafe fn fetch(
+        components: &ComponentStorage,
+        state: Self::State,
+        key: ItemKey,
+    ) -> Option<Self::Item<'_>>;
+}
+
+macro_rules! impl_query {
+    ($($name:ident),+) => {
+        impl<$($name: Fetch),+> Query for ($($name,)+) {
+            type Item<'world> = ($($name::Item<'world>,)+);
+            type State = ($(impl_query!(@state $name),)+);
+
+            fn accesses() -> Vec<(TypeId, &'static str, bool)> {
+                vec![$((
+                    TypeId::of::<$name::Component>(),
+                    type_name::<$name::Component>(),
+                    $name::MUTABLE,
+                )),+]
+            }
+
+            fn state(components: &ComponentStorage) -> Option<Self::State> {
+                Some(($(components.try_mini_type_of::<$name::Component>()?,)+))
+            }
+
+            fn driver(components: &ComponentStorage) -> Box<dyn Iterator<Item = ItemKey> + '_> {
+                let lens = [$(components.keys::<$name::Component>().len()),+];
+                let smallest = (0..lens.len())
+                    .min_by_key(|&i| lens[i])
+                    .expect("queries access at least one component");
+                let mut i = 0;
+                $(
+                    if i == smallest {
+                        return Box::new(components.keys::<$name::Component>().copied());
+                    }
+                    i += 1;
+                )+
+                unreachable!("{i} columns, smallest is {smallest}")
+            }
+
+            #[allow(non_snake_case)]
+            unsafe fn fetch(
+                components: &ComponentStorage,
+                state: Self::State,
+                key: ItemKey,
+            ) -> Option<Self::Item<'_>> {
+                let ($($name,)+) = state;
+                // Safety: upheld by the caller.
+                unsafe { Some(($($name::fetch(components, $name, key)?,)+)) }
+            }
+        }
+    };
+    (@state $name:ident) => {
+        MiniTypeId
+    };
+}
+
+impl_query!(A);
+impl_query!(A, B);
+impl_query!(A, B, C);
+impl_query!(A, B, C, D);
+impl_query!(A, B, C, D, E);
+impl_query!(A, B, C, D, E, F);
+
+impl World {
+    /// Starts a [`NodeQuery`] matching every active node, to be narrowed down
+    /// with its filters.
+    ///
+    /// # Example
+    /// ```
+    /// # use necs::{Node, World, node};
+    /// struct Frozen;
+    ///
+    /// #[node]
+    /// struct Ice {
+    ///     #[ext]
+    ///     frozen: Frozen,
+    /// }
+    ///
+    /// #[node]
+    /// struct Water {
+    ///     depth: f32,
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut world = World::new();
+    /// world.spawn_node(IceBuilder { frozen: Frozen });
+    /// let water = world.spawn_node(WaterBuilder { depth: 2.0 });
+    ///
+    /// let ids: Vec<_> = world
+    ///     .query_nodes()
+    ///     .implementing::<dyn Node>()
+    ///     .without_ext::<Frozen>()
+    ///     .ids()
+    ///     .collect();
+    /// assert_eq!(ids, [water]);
+    /// # }
+    /// ```
+    pub fn query_nodes(&self) -> NodeQuery<'_> {
+        NodeQuery::new(self)
+    }
+
+    /// Iterates over every active node having all the `#[ext]` components
+    /// accessed by [Q], whatever their node type, yielding them along with
+    /// their ids.
+    ///
+    /// Only the smallest of the accessed component columns is walked, so
+    /// joining a rare component with a common one stays cheap.
+    ///
+    /// # Panics
+    /// Panics if [Q] accesses a component type mutably more than once, or
+    /// both mutably and immutably, as well as when reaching a node that is
+    /// currently borrowed. As this takes `&mut self`, a node can only be
+    /// borrowed here through a handle returned by
+    /// [`get_node_resilient`](World::get_node_resilient), which is not tied to
+    /// the lifetime of the world.
+    ///
+    /// # Example
+    /// ```
+    /// # use necs::{World, node};
+    /// struct Position(f32);
+    /// struct Velocity(f32);
+    ///
+    /// #[node]
+    /// struct Body {
+    ///     #[ext]
+    ///     position: Position,
+    ///     #[ext]
+    ///     velocity: Velocity,
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut world = World::new();
+    /// world.spawn_node(BodyBuilder {
+    ///     position: Position(0.0),
+    ///     velocity: Velocity(2.0),
+    /// });
+    /// for (_, (position, velocity)) in world.query::<(&mut Position, &Velocity)>() {
+    ///     position.0 += velocity.0;
+    /// }
+    /// # }
+    /// ```
+    pub fn query<Q: Query>(&mut self) -> impl Iterator<Item = (NodeId, Q::Item<'_>)> {
+        let accesses = Q::accesses();
+        for (i, &(type_id, name, mutable)) in accesses.iter().enumerate() {
+            let aliased = accesses[i + 1..]
+                .iter()
+                .any(|&(other, _, other_mutable)| other == type_id && (mutable || other_mutable));
+            if aliased {
+                panic!("query would alias a mutable reference to {name}");
+            }
+        }
+
+        let world = &*self;
+        let components = &world.storage.components;
+        let check_active = !world.all_active();
+        Q::state(components).into_iter().flat_map(move |state| {
+            Q::driver(components).filter_map(move |key| {
+                let id = world
+                    .storage
+                    .nodes
+                    .id_of(key)
+                    .expect("components should always belong to a node");
+                if check_active && !world.is_active(id) {
+                    return None;
+                }
+                world.assert_not_borrowed(id);
+                // Safety: no component is accessed mutably twice, each key is only
+                // yielded once, and the exclusive borrow of the world keeps anything
+                // else from accessing the components.
+                let item = unsafe { Q::fetch(components, state, key) }?;
+                Some((id, item))
+            })
+        })
+    }
+}
+
+/// A composable filter over the nodes of a [`World`], created with
+/// [`World::query_nodes`].
+///
+/// Filters on node types, traits and `#[ext]` components are resolved once
+/// into a set of matching node types. Only the nodes of those types are
+/// visited, and they are then only checked against their activity and the
+/// [`filter`](NodeQuery::filter) predicates.
+pub struct NodeQuery<'world> {
+    world: &'world World,
+    // Whether each node type, indexed by its MiniTypeId, passes the type-level filters.
+    node_types: Vec<bool>,
+    include_inactive: bool,
+    predicates: Vec<Box<dyn Fn(NodeId) -> bool + 'world>>,
+}
+
+impl Debug for NodeQuery<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeQuery")
+            .field("node_types", &self.node_types)
+            .field("include_inactive", &self.include_inactive)
+            .field("predicates", &self.predicates.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'world> NodeQuery<'world> {
+    fn new(world: &'world World) -> Self {
+        let mut node_types = Vec::new();
+        for node_type in world.storage.nodes.node_types() {
+            node_types.resize(node_type.index() + 1, false);
+            node_types[node_type.index()] = true;
+        }
+        Self {
+            world,
+            node_types,
+            include_inactive: false,
+            predicates: Vec::new(),
+        }
+    }
+
+    /// Only keeps the node types for which `keep` returns true.
+    fn retain_types(mut self, mut keep: impl FnMut(MiniTypeId) -> bool) -> Self {
+        for (index, matches) in self.node_types.iter_mut().enumerate() {
+            *matches = *matches && keep(MiniTypeId::from(index));
+        }
+        self
+    }
+
+    /// Only matches nodes of concrete type [T].
+    pub fn of_type<T: NodeRef>(self) -> Self {
+        let node_type = self.world.storage.nodes.try_mini_type_of::<T>();
+        self.retain_types(|other| Some(other) == node_type)
+    }
+
+    /// Only matches nodes having an `#[ext]` field of type [C].
+    pub fn with_ext<C: Send + Sync + 'static>(self) -> Self {
+        let world = self.world;
+        let component_type = world.storage.components.try_mini_type_of::<C>();
+        self.retain_types(|node_type| {
+            component_type.is_some_and(|component_type| {
+                world
+                    .storage
+                    .nodes
+                    .component_types(node_type)
+                    .contains(&component_type)
+            })
+        })
+    }
+
+    /// Only matches nodes without any `#[ext]` field of type [C].
+    pub fn without_ext<C: Send + Sync + 'static>(self) -> Self {
+        let world = self.world;
+        let component_type = world.storage.components.try_mini_type_of::<C>();
+        self.retain_types(|node_type| {
+            component_type.is_none_or(|component_type| {
+                !world
+                    .storage
+                    .nodes
+                    .component_types(node_type)
+                    .contains(&component_type)
+            })
+        })
+    }
+
+    /// Only matches nodes whose type was registered for `Trait` with
+    /// [`World::register_trait`].
+    pub fn implementing<Trait: NodeTrait + ?Sized + 'static>(self) -> Self {
+        let implementors: Vec<MiniTypeId> = self
+            .world
+            .trait_map
+            .implementors::<Trait>()
+            .map(|(node_type, _)| node_type)
+            .collect();
+        self.retain_types(|node_type| implementors.contains(&node_type))
+    }
+
+    /// Also matches inactive nodes, see [`World::is_active`].
+    pub fn include_inactive(mut self) -> Self {
+        self.include_inactive = true;
+        self
+    }
+
+    /// Only matches nodes for which `predicate` returns true. Predicates run
+    /// after every other filter, in the order they were added.
+    pub fn filter(mut self, predicate: impl Fn(NodeId) -> bool + 'world) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Iterates over the ids of the matching nodes, skipping taken ones.
+    pub fn ids(self) -> impl Iterator<Item = NodeId> + 'world {
+        let world = self.world;
+        let nodes = &world.storage.nodes;
+        let check_active = !self.include_inactive && !world.all_active();
+        // Every node of a matching type has the #[ext] fields required by with_ext, so
+        // the sub-maps of those types never hold more nodes than the columns would.
+        let node_types = (self.node_types.into_iter().enumerate())
+            .filter(|&(_, matches)| matches)
+            .map(|(index, _)| MiniTypeId::from(index));
+        let candidates = node_types.flat_map(|node_type| nodes.stored_ids(node_type));
+        let predicates = self.predicates;
+        candidates.filter(move |&id| {
+            (!check_active || world.is_active(id))
+                && predicates.iter().all(|predicate| predicate(id))
+        })
+    }
+
+    /// Lazily iterates over the matching nodes of concrete type [T], skipping
+    /// taken ones.
+    ///
+    /// # Panics
+    /// Panics when reaching a node that is already borrowed.
+    pub fn nodes<T: NodeRef>(self) -> impl Iterator<Item = T::Instance<'world>> {
+        let world = self.world;
+        let check_active = !self.include_inactive && !world.all_active();
+        let query = self.of_type::<T>();
+        // Only T can match, so its cells are walked directly if it passes the filters.
+        let matches = query.node_types.contains(&true);
+        let predicates = query.predicates;
+        let node_cells = world.storage.nodes.iter_cells::<T>();
+        node_cells
+            .filter(move |&(id, _)| {
+                matches
+                    && (!check_active || world.is_active(id))
+                    && predicates.iter().all(|predicate| predicate(id))
+            })
+            .map(move |(id, node_cell)| world.build_node::<T>(id, node_cell))
+    }
+
+    /// Lazily iterates over the matching nodes whose type was registered for
+    /// [T], along with their ids, skipping taken ones.
+    ///
+    /// # Panics
+    /// Panics when reaching a node that is already borrowed.
+    pub fn nodes_resilient<T: NodeTrait + ?Sized + 'static>(
+        self,
+    ) -> impl Iterator<Item = (NodeId, Box<T>)> + 'world {
+        let world = self.world;
+        // Look up how to get each implementing type as T once, rather than per node.
+        let mut getters = Vec::new();
+        for (node_type, get_node) in world.trait_map.implementors::<T>() {
+            getters.resize_with(getters.len().max(node_type.index() + 1), || None);
+            getters[node_type.index()] = Some(get_node);
+        }
+        self.implementing::<T>().ids().filter_map(move |id| {
+            let get_node = getters[id.node_type.index()]
+                .as_ref()
+                .expect("only implementing node types are matched");
+            match get_node(&world.storage, id) {
+                Ok(node) => Some((id, node)),
+                Err(NecsError::Taken(_)) => None,
+                Err(e) => panic!("{}", e),
+            }
+        })
+    }
+}
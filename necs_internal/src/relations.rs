@@ -1,9 +1,14 @@
-use crate::ItemKey;
+use crate::{ItemKey, ProcessMode};
 
 #[derive(Debug)]
 pub struct Relations {
     parent: Option<ItemKey>,
     children: Vec<ItemKey>,
+    // Unique among siblings, used to look nodes up by path.
+    name: Option<String>,
+    // Apply to the whole subtree, see World::is_active().
+    enabled: bool,
+    process_mode: ProcessMode,
 }
 
 impl Relations {
@@ -11,6 +16,64 @@ impl Relations {
         Self {
             parent,
             children: Vec::default(),
+            name: None,
+            enabled: true,
+            process_mode: ProcessMode::Inherit,
         }
     }
+
+    pub fn parent(&self) -> Option<ItemKey> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[ItemKey] {
+        &self.children
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub(crate) fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn process_mode(&self) -> ProcessMode {
+        self.process_mode
+    }
+
+    pub(crate) fn set_process_mode(&mut self, process_mode: ProcessMode) {
+        self.process_mode = process_mode;
+    }
+
+    /// Returns whether the flags of this node may make it or its descendants
+    /// inactive.
+    pub(crate) fn is_flagged(&self) -> bool {
+        !self.enabled || self.process_mode != ProcessMode::Inherit
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Option<ItemKey>) {
+        self.parent = parent;
+    }
+
+    pub(crate) fn push_child(&mut self, child: ItemKey) {
+        self.children.push(child);
+    }
+
+    /// Inserts `child` at `index`, or last if `index` is past the end.
+    pub(crate) fn insert_child(&mut self, index: usize, child: ItemKey) {
+        self.children.insert(index.min(self.children.len()), child);
+    }
+
+    pub(crate) fn remove_child(&mut self, child: ItemKey) {
+        self.children.retain(|&key| key != child);
+    }
 }
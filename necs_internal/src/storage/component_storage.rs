@@ -46,6 +46,88 @@ impl<'a> ComponentStorage {
         unsafe { ComponentId::new(self.0.mini_type_of::<T>(), key) }
     }
 
+    /// Returns the [`MiniTypeId`] of component type [`T`].
+    ///
+    /// # Panics
+    ///
+    /// [`T`] must be registered with [`Self::register`] before calling this
+    /// function.
+    pub fn mini_type_of<T>(&self) -> MiniTypeId
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0.mini_type_of::<T>()
+    }
+
+    /// Returns the [`MiniTypeId`] of component type [`T`], or [`None`] if it
+    /// is not registered.
+    pub fn try_mini_type_of<T>(&self) -> Option<MiniTypeId>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0.try_mini_type_of::<T>()
+    }
+
+    /// Returns the keys of every component of type [`T`].
+    pub fn keys<T>(&self) -> impl ExactSizeIterator<Item = &ItemKey>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.0.keys::<T, _>()
+    }
+
+    /// Returns whether a component of type [`T`] is stored under `key`.
+    pub fn contains<T>(&self, key: ItemKey) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.try_mini_type_of::<T>().is_some_and(|component_type| {
+            // Safety: component_type was just looked up for T.
+            unsafe { self.0.get_unchecked::<T, _>(component_type, key) }.is_some()
+        })
+    }
+
+    /// Inserts the given component into storage under an already resolved
+    /// [`ComponentId`], skipping the type lookup done by [`Self::insert`].
+    pub fn insert_with_id<T>(&mut self, id: &ComponentId<T>, component: T)
+    where
+        T: 'static + Send + Sync,
+    {
+        // Safety: the component type of the id is guaranteed to correspond to T.
+        unsafe {
+            self.0
+                .insert_unchecked::<T, _>(id.into(), id.into(), SyncUnsafeCell::new(component))
+        };
+    }
+
+    /// Reserves capacity for at least `additional` more components of type
+    /// [`T`].
+    ///
+    /// # Panics
+    ///
+    /// [`T`] must be registered with [`Self::register`] before calling this
+    /// function.
+    pub fn reserve<T>(&mut self, additional: usize)
+    where
+        T: 'static + Send + Sync,
+    {
+        self.0.reserve::<T, _>(additional);
+    }
+
+    /// Removes the component of type [`T`] stored under `key`, returning it if
+    /// it existed.
+    ///
+    /// # Panics
+    ///
+    /// [`T`] must be registered with [`Self::register`] before calling this
+    /// function.
+    pub fn remove<T>(&mut self, key: ItemKey) -> Option<T>
+    where
+        T: 'static + Send + Sync,
+    {
+        self.0.remove::<T, _>(key).map(SyncUnsafeCell::into_inner)
+    }
+
     /// Gets a mutable reference to an element of type `T` from the internal map
     /// using an unchecked operation.
     ///
@@ -93,6 +175,28 @@ impl<'a> ComponentStorage {
         }
     }
 
+    /// Like [`Self::get_element_unchecked`], but returns [`None`] instead of
+    /// panicking if the component does not exist.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee there are no aliasing mutable or immutable
+    /// references to the same component at the same time.
+    #[allow(clippy::mut_from_ref)]
+    #[inline(always)]
+    pub unsafe fn try_get_element_unchecked<T: 'static + Send + Sync>(
+        &self,
+        id: &ComponentId<T>,
+    ) -> Option<&mut T> {
+        // Safety: the type of the downcast is guaranteed to be correct since it is
+        // based on the same type as the key.
+        unsafe {
+            self.0
+                .get_unchecked::<T, _>(id.into(), id.into())
+                .map(|cell| cell.get().as_mut_unchecked())
+        }
+    }
+
     pub fn get_element<T: 'static + Send + Sync>(&self, id: &ItemKey) -> &'a mut T {
         unsafe {
             self.0
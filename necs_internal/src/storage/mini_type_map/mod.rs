@@ -32,7 +32,8 @@ impl MiniTypeMap {
     /// We can get the [`MiniTypeId`] of [`T`] using [`Self::mini_type_of`].
     pub fn register<T: MiniTypeMapKey<D>, D>(&mut self) -> MiniTypeId {
         let type_id = TypeId::of::<T>();
-        let next_idx = self.id_map.len();
+        // Not id_map.len(), since unregistered types leave their slot behind.
+        let next_idx = self.data.len();
         let entry = self.id_map.entry(type_id).or_insert_with(|| {
             let mini_type_id = MiniTypeId::from(next_idx);
             let sub_map: HashMap<ItemKey, T::Value> = HashMap::default();
@@ -42,15 +43,31 @@ impl MiniTypeMap {
         *entry
     }
 
+    /// Unregisters type [`T`] from this map, dropping all of its items and
+    /// returning the [`MiniTypeId`] it had, if it was registered.
+    ///
+    /// Since [`MiniTypeId`]s are indices, the slot of [`T`] is left empty
+    /// rather than shifting other types, and is never handed out again.
+    pub fn unregister<T: 'static>(&mut self) -> Option<MiniTypeId> {
+        let mini_type_id = self.id_map.remove(&TypeId::of::<T>())?;
+        self.data[mini_type_id.index()] = Box::new(());
+        Some(mini_type_id)
+    }
+
     /// Returns the [`MiniTypeId`] corresponding to [`T`].
     #[inline]
     pub fn mini_type_of<T: 'static>(&self) -> MiniTypeId {
-        *self
-            .id_map
-            .get(&TypeId::of::<T>())
+        self.try_mini_type_of::<T>()
             .unwrap_or_else(|| type_not_registered::<T>())
     }
 
+    /// Returns the [`MiniTypeId`] corresponding to [`T`], or [`None`] if [`T`]
+    /// is not registered.
+    #[inline]
+    pub fn try_mini_type_of<T: 'static>(&self) -> Option<MiniTypeId> {
+        self.id_map.get(&TypeId::of::<T>()).copied()
+    }
+
     #[inline]
     pub fn insert<T: MiniTypeMapKey<D>, D>(&mut self, key: ItemKey, item: T::Value) {
         let mini_type_id = self.mini_type_of::<T>();
@@ -66,53 +83,128 @@ impl MiniTypeMap {
         sub_map.insert(key, item);
     }
 
+    /// Inserts an item into the sub-map of `mini_type_id` without looking up
+    /// the [`MiniTypeId`] of [`T`].
+    ///
+    /// # Safety
+    ///
+    /// `mini_type_id` must be the [`MiniTypeId`] of [`T`].
     #[inline]
-    pub fn keys<T: MiniTypeMapKey<D>, D>(&self) -> impl ExactSizeIterator<Item = &ItemKey> {
+    pub unsafe fn insert_unchecked<T: MiniTypeMapKey<D>, D>(
+        &mut self,
+        mini_type_id: MiniTypeId,
+        key: ItemKey,
+        item: T::Value,
+    ) {
+        let sub_map = unsafe {
+            self.data
+                .get_mut(mini_type_id.index())
+                .unwrap_or_else(|| type_not_registered::<T>())
+                // SAFETY: the caller guarantees T corresponds to mini_type_id.
+                .downcast_unchecked_mut::<HashMap<ItemKey, T::Value>>()
+        };
+        sub_map.insert(key, item);
+    }
+
+    /// Reserves capacity for at least `additional` more items of type [`T`].
+    #[inline]
+    pub fn reserve<T: MiniTypeMapKey<D>, D>(&mut self, additional: usize) {
         let mini_type_id = self.mini_type_of::<T>();
         let sub_map = unsafe {
             // SAFETY: The call to mini_type_of() would have panicked if the type wasn't
             // registered.
             self.data
-                .get_unchecked(mini_type_id.index())
+                .get_unchecked_mut(mini_type_id.index())
                 // SAFETY: We know this is the correct type because both the key and value are
                 // derived from the same type.
-                .downcast_unchecked_ref::<HashMap<ItemKey, T::Value>>()
+                .downcast_unchecked_mut::<HashMap<ItemKey, T::Value>>()
         };
-        sub_map.keys()
+        sub_map.reserve(additional);
     }
 
     #[inline]
-    pub fn values<T: MiniTypeMapKey<D>, D>(&self) -> impl ExactSizeIterator<Item = &T::Value> {
+    pub fn remove<T: MiniTypeMapKey<D>, D>(&mut self, key: ItemKey) -> Option<T::Value> {
         let mini_type_id = self.mini_type_of::<T>();
         let sub_map = unsafe {
             // SAFETY: The call to mini_type_of() would have panicked if the type wasn't
             // registered.
+            self.data
+                .get_unchecked_mut(mini_type_id.index())
+                // SAFETY: We know this is the correct type because both the key and value are
+                // derived from the same type.
+                .downcast_unchecked_mut::<HashMap<ItemKey, T::Value>>()
+        };
+        sub_map.remove(&key)
+    }
+
+    /// Returns the sub-map of [`T`], or [`None`] if [`T`] is not registered.
+    #[inline]
+    fn sub_map<T: MiniTypeMapKey<D>, D>(&self) -> Option<&HashMap<ItemKey, T::Value>> {
+        let mini_type_id = self.try_mini_type_of::<T>()?;
+        let sub_map = unsafe {
+            // SAFETY: mini_type_id was just looked up, so the type is registered.
             self.data
                 .get_unchecked(mini_type_id.index())
                 // SAFETY: We know this is the correct type because both the key and value are
                 // derived from the same type.
                 .downcast_unchecked_ref::<HashMap<ItemKey, T::Value>>()
         };
-        sub_map.values()
+        Some(sub_map)
     }
 
+    /// Mutably returns the sub-map of [`T`], or [`None`] if [`T`] is not
+    /// registered.
     #[inline]
-    pub fn values_mut<T: MiniTypeMapKey<D>, D>(
-        &mut self,
-    ) -> impl ExactSizeIterator<Item = &mut T::Value> {
-        let mini_type_id = self.mini_type_of::<T>();
+    fn sub_map_mut<T: MiniTypeMapKey<D>, D>(&mut self) -> Option<&mut HashMap<ItemKey, T::Value>> {
+        let mini_type_id = self.try_mini_type_of::<T>()?;
         let sub_map = unsafe {
-            // SAFETY: The call to mini_type_of() would have panicked if the type wasn't
-            // registered.
+            // SAFETY: mini_type_id was just looked up, so the type is registered.
             self.data
                 .get_unchecked_mut(mini_type_id.index())
                 // SAFETY: We know this is the correct type because both the key and value are
                 // derived from the same type.
                 .downcast_unchecked_mut::<HashMap<ItemKey, T::Value>>()
         };
-        sub_map.values_mut()
+        Some(sub_map)
+    }
+
+    /// Iterates over the keys of every item of type [`T`], yielding nothing if
+    /// [`T`] is not registered.
+    #[inline]
+    pub fn keys<T: MiniTypeMapKey<D>, D>(&self) -> impl ExactSizeIterator<Item = &ItemKey> {
+        MaybeEmpty(self.sub_map::<T, D>().map(HashMap::keys))
+    }
+
+    /// Iterates over every item of type [`T`], yielding nothing if [`T`] is not
+    /// registered.
+    #[inline]
+    pub fn values<T: MiniTypeMapKey<D>, D>(&self) -> impl ExactSizeIterator<Item = &T::Value> {
+        MaybeEmpty(self.sub_map::<T, D>().map(HashMap::values))
+    }
+
+    /// Iterates over every item of type [`T`] along with its key, yielding
+    /// nothing if [`T`] is not registered.
+    #[inline]
+    pub fn iter<T: MiniTypeMapKey<D>, D>(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (&ItemKey, &T::Value)> {
+        MaybeEmpty(self.sub_map::<T, D>().map(HashMap::iter))
+    }
+
+    /// Mutably iterates over every item of type [`T`], yielding nothing if
+    /// [`T`] is not registered.
+    #[inline]
+    pub fn values_mut<T: MiniTypeMapKey<D>, D>(
+        &mut self,
+    ) -> impl ExactSizeIterator<Item = &mut T::Value> {
+        MaybeEmpty(self.sub_map_mut::<T, D>().map(HashMap::values_mut))
     }
 
+    /// Gets the value stored under `key` in the sub-map of `mini_type_id`.
+    ///
+    /// # Safety
+    ///
+    /// `mini_type_id` must be the [`MiniTypeId`] of [`T`].
     #[inline]
     pub unsafe fn get_unchecked<T: MiniTypeMapKey<D>, D>(
         &self,
@@ -129,6 +221,12 @@ impl MiniTypeMap {
         sub_map.get(&key)
     }
 
+    /// Mutably gets the value stored under `key` in the sub-map of
+    /// `mini_type_id`.
+    ///
+    /// # Safety
+    ///
+    /// `mini_type_id` must be the [`MiniTypeId`] of [`T`].
     #[inline]
     pub unsafe fn get_mut_unchecked<T: MiniTypeMapKey<D>, D>(
         &mut self,
@@ -146,6 +244,27 @@ impl MiniTypeMap {
     }
 }
 
+/// An iterator over a sub-map that may not exist, in which case it is empty.
+struct MaybeEmpty<I>(Option<I>);
+
+impl<I: Iterator> Iterator for MaybeEmpty<I> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.as_mut()?.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0
+            .as_ref()
+            .map_or((0, Some(0)), |iter| iter.size_hint())
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for MaybeEmpty<I> {}
+
 pub trait MiniTypeMapKey<Disambiguator>: 'static {
     type Value: Send + Sync + 'static;
 }
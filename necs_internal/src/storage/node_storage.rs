@@ -1,13 +1,20 @@
 use crate::ItemKey;
-use crate::storage::{MiniTypeId, MiniTypeMap};
-use crate::{NodeId, NodeRef};
+use crate::storage::{MiniTypeId, MiniTypeMap, Storage};
+use crate::{CloneNode, NecsError, NodeBuilder, NodeId, NodeRef};
 use core::panic;
 use slotmap::SlotMap;
+use std::any::type_name;
 use std::cell::SyncUnsafeCell;
 use std::marker::PhantomPinned;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 
+#[cold]
+#[inline(never)]
+fn access_failed(error: NecsError) -> ! {
+    panic!("{}", error)
+}
+
 /// For use by the #[node] macro, this drops runtime borrows.
 pub struct BorrowDropper<'a>(&'a AtomicBool, PhantomPinned);
 
@@ -31,11 +38,63 @@ pub struct RecipeTupleCell<T> {
     borrowed: AtomicBool,
 }
 
+impl<T> RecipeTupleCell<T> {
+    /// Borrows the node's data until the returned [`BorrowDropper`] is
+    /// dropped, or returns [`None`] if it is already borrowed.
+    #[inline]
+    #[allow(clippy::mut_from_ref)] // We do our own borrow checking.
+    pub fn try_borrow(&self) -> Option<(&mut T, BorrowDropper<'_>)> {
+        self.borrowed
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .ok()?;
+        // Safety: the borrowed flag guarantees no other reference to the recipe tuple
+        // exists until the BorrowDropper is dropped.
+        let recipe_tuple = unsafe { self.recipe_tuple.get().as_mut_unchecked() };
+        Some((recipe_tuple, BorrowDropper::new(&self.borrowed)))
+    }
+}
+
+/// Removes a node of a specific type, see [`NodeRef::__remove_from_storage`].
+type RemoveFn = fn(&mut Storage, NodeId) -> Result<(), NecsError>;
+/// Checks whether a node of a specific type is borrowed, see
+/// [`NodeStorage::is_borrowed`].
+type IsBorrowedFn = fn(&NodeStorage, NodeId) -> bool;
+/// Iterates over the stored nodes of a specific type, see
+/// [`NodeStorage::stored_ids`].
+type IdsFn = for<'a> fn(&'a NodeStorage) -> Box<dyn ExactSizeIterator<Item = NodeId> + 'a>;
+/// Borrows a node of a specific type, see [`NodeStorage::try_borrow`].
+type BorrowFn = for<'a> fn(&'a NodeStorage, NodeId) -> Result<BorrowDropper<'a>, NecsError>;
+/// Clones a node of a specific type, see [`CloneNode::__clone_builder`].
+type CloneFn = fn(&mut Storage, NodeId) -> Result<NodeId, NecsError>;
+
+/// Operations on a registered node type that can be performed without knowing
+/// the type.
+#[derive(Debug)]
+pub(crate) struct NodeVTable {
+    pub(crate) remove: RemoveFn,
+    pub(crate) is_borrowed: IsBorrowedFn,
+    pub(crate) borrow: BorrowFn,
+    pub(crate) ids: IdsFn,
+    // Only present for node types implementing CloneNode.
+    pub(crate) clone: Option<CloneFn>,
+    // The component types of the node's #[ext] fields, in declaration order.
+    component_types: Box<[MiniTypeId]>,
+}
+
+fn clone_erased<T: CloneNode>(storage: &mut Storage, id: NodeId) -> Result<NodeId, NecsError> {
+    let node = T::__clone_builder(storage, id)?;
+    Ok(node.__move_to_storage(storage))
+}
+
 #[derive(Debug)]
 pub struct NodeStorage {
-    // This map only serves to generate unique keys.
-    key_factory: SlotMap<ItemKey, ()>,
+    // Generates unique keys and tracks the type of the node each key belongs to,
+    // its generations let us tell live keys from despawned ones.
+    key_factory: SlotMap<ItemKey, MiniTypeId>,
     nodes: MiniTypeMap,
+    // Indexed by MiniTypeId, allows working on nodes without knowing their type. Unregistered
+    // types leave None behind.
+    vtables: Vec<Option<NodeVTable>>,
 }
 
 impl NodeStorage {
@@ -43,20 +102,181 @@ impl NodeStorage {
         Self {
             key_factory: SlotMap::default(),
             nodes: MiniTypeMap::default(),
+            vtables: Vec::new(),
         }
     }
 
-    fn mint_key(&mut self) -> ItemKey {
-        self.key_factory.insert(())
+    fn mint_key(&mut self, node_type: MiniTypeId) -> ItemKey {
+        self.key_factory.insert(node_type)
+    }
+
+    /// Returns whether the given [`NodeId`] refers to a node that currently
+    /// exists.
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.key_factory.get(id.instance) == Some(&id.node_type)
+    }
+
+    /// Ensures the given [`NodeId`] refers to a node that currently exists,
+    /// failing with [`NecsError::TypeChanged`] if the node exists but its type
+    /// was changed.
+    pub fn check(&self, id: NodeId) -> Result<(), NecsError> {
+        match self.key_factory.get(id.instance) {
+            Some(&node_type) if node_type == id.node_type => Ok(()),
+            Some(&node_type) => Err(NecsError::TypeChanged {
+                id,
+                current: NodeId {
+                    node_type,
+                    instance: id.instance,
+                },
+            }),
+            None => Err(NecsError::StaleId(id)),
+        }
     }
 
     pub fn mini_type_of<T: NodeRef>(&self) -> MiniTypeId {
         self.nodes.mini_type_of::<T>()
     }
 
-    /// Registers a node type if it does not exist already.
-    pub fn register<T: NodeRef>(&mut self) {
-        self.nodes.register::<T, _>();
+    pub fn try_mini_type_of<T: NodeRef>(&self) -> Option<MiniTypeId> {
+        self.nodes.try_mini_type_of::<T>()
+    }
+
+    /// Registers a node type if it does not exist already, along with the
+    /// component types of its `#[ext]` fields.
+    pub fn register<T: NodeRef>(&mut self, component_types: Vec<MiniTypeId>) {
+        let node_type = self.nodes.register::<T, _>();
+        if node_type.index() == self.vtables.len() {
+            self.vtables.push(Some(NodeVTable {
+                remove: T::__remove_from_storage,
+                is_borrowed: Self::is_borrowed::<T>,
+                borrow: Self::borrow::<T>,
+                ids: Self::ids_erased::<T>,
+                clone: None,
+                component_types: component_types.into_boxed_slice(),
+            }));
+        }
+    }
+
+    /// Unregisters a node type, returning the [`MiniTypeId`] it had.
+    ///
+    /// All nodes of type [`T`] must have been despawned beforehand.
+    pub fn unregister<T: NodeRef>(&mut self) -> Option<MiniTypeId> {
+        let node_type = self.nodes.unregister::<T>()?;
+        self.vtables[node_type.index()] = None;
+        Some(node_type)
+    }
+
+    /// Returns the ids of every node, including nodes that were taken.
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        self.key_factory.iter().map(|(key, &node_type)| NodeId {
+            node_type,
+            instance: key,
+        })
+    }
+
+    /// Returns the ids of every node of the given type, including nodes that
+    /// were taken.
+    pub fn ids_of_type(&self, node_type: MiniTypeId) -> impl Iterator<Item = NodeId> {
+        self.key_factory
+            .iter()
+            .filter(move |&(_, &key_type)| key_type == node_type)
+            .map(move |(key, _)| NodeId {
+                node_type,
+                instance: key,
+            })
+    }
+
+    /// Returns the ids of every node of the given type that was not taken,
+    /// from the type's own sub-map. Yields nothing if no such type is
+    /// registered.
+    pub fn stored_ids(
+        &self,
+        node_type: MiniTypeId,
+    ) -> Box<dyn ExactSizeIterator<Item = NodeId> + '_> {
+        match self.vtable_of(node_type) {
+            Some(vtable) => (vtable.ids)(self),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn ids_erased<T: NodeRef>(&self) -> Box<dyn ExactSizeIterator<Item = NodeId> + '_> {
+        Box::new(self.get_ids::<T>())
+    }
+
+    /// Returns whether the given node of type [`T`] is currently borrowed.
+    /// Nodes that don't exist or were taken are never borrowed.
+    pub fn is_borrowed<T: NodeRef>(&self, id: NodeId) -> bool {
+        self.get_cell::<T>(id)
+            .is_ok_and(|node_cell| node_cell.borrowed.load(Acquire))
+    }
+
+    /// Borrows the node associated with the given [`NodeId`] until the
+    /// returned [`BorrowDropper`] is dropped, without knowing its type.
+    ///
+    /// Fails if the node's type is not registered, the node does not exist,
+    /// was taken or is already borrowed.
+    pub fn try_borrow(&self, id: NodeId) -> Result<BorrowDropper<'_>, NecsError> {
+        let vtable = self.vtable_of(id.node_type).ok_or(NecsError::StaleId(id))?;
+        (vtable.borrow)(self, id)
+    }
+
+    fn borrow<T: NodeRef>(&self, id: NodeId) -> Result<BorrowDropper<'_>, NecsError> {
+        self.try_get_element::<T>(id)
+            .map(|(_, borrow_dropper)| borrow_dropper)
+    }
+
+    /// Returns every registered node type.
+    pub fn node_types(&self) -> impl Iterator<Item = MiniTypeId> {
+        self.vtables
+            .iter()
+            .enumerate()
+            .filter(|(_, vtable)| vtable.is_some())
+            .map(|(index, _)| MiniTypeId::from(index))
+    }
+
+    /// Returns the component types of the `#[ext]` fields of the given node
+    /// type, in declaration order.
+    ///
+    /// # Panics
+    /// The node type must be registered.
+    #[inline]
+    pub fn component_types(&self, node_type: MiniTypeId) -> &[MiniTypeId] {
+        &self.vtables[node_type.index()]
+            .as_ref()
+            .expect("node type should be registered")
+            .component_types
+    }
+
+    /// Allows nodes of type [`T`] to be cloned without knowing their type.
+    ///
+    /// # Panics
+    /// [`T`] must be registered.
+    pub fn register_clone<T: CloneNode>(&mut self) {
+        let node_type = self.nodes.mini_type_of::<T>();
+        if let Some(vtable) = &mut self.vtables[node_type.index()] {
+            vtable.clone = Some(clone_erased::<T>);
+        }
+    }
+
+    /// Returns the type-erased operations of the given node type, or [`None`]
+    /// if no such type is registered.
+    pub(crate) fn vtable_of(&self, node_type: MiniTypeId) -> Option<&NodeVTable> {
+        self.vtables.get(node_type.index())?.as_ref()
+    }
+
+    /// Returns the [`NodeId`] of the node that the given key belongs to, or
+    /// [`None`] if the key was freed.
+    pub fn id_of(&self, key: ItemKey) -> Option<NodeId> {
+        self.key_factory.get(key).map(|&node_type| NodeId {
+            node_type,
+            instance: key,
+        })
+    }
+
+    /// Reserves capacity for at least `additional` more nodes of type [`T`].
+    pub fn reserve<T: NodeRef>(&mut self, additional: usize) {
+        self.key_factory.reserve(additional);
+        self.nodes.reserve::<T, _>(additional);
     }
 
     /// Inserts a [T::RecipeTuple] into the storage.
@@ -64,47 +284,203 @@ impl NodeStorage {
     where
         T: NodeRef,
     {
-        let key = self.mint_key();
         let node_type = self.nodes.mini_type_of::<T>();
-        self.nodes.insert::<T, _>(
-            key,
-            RecipeTupleCell {
-                recipe_tuple: SyncUnsafeCell::new(node),
-                borrowed: AtomicBool::new(false),
-            },
-        );
+        // Safety: node_type was just looked up from T.
+        unsafe { self.spawn_as::<T>(node_type, node) }
+    }
+
+    /// Inserts a [T::RecipeTuple] into the storage, skipping the type lookup
+    /// done by [`Self::spawn`].
+    ///
+    /// # Safety
+    /// `node_type` must be the [`MiniTypeId`] of [`T`].
+    pub unsafe fn spawn_as<T>(&mut self, node_type: MiniTypeId, node: T::RecipeTuple) -> NodeId
+    where
+        T: NodeRef,
+    {
+        let key = self.mint_key(node_type);
+        unsafe {
+            // SAFETY: the caller guarantees node_type corresponds to T.
+            self.nodes.insert_unchecked::<T, _>(
+                node_type,
+                key,
+                RecipeTupleCell {
+                    recipe_tuple: SyncUnsafeCell::new(node),
+                    borrowed: AtomicBool::new(false),
+                },
+            );
+        }
         NodeId {
             node_type,
             instance: key,
         }
     }
 
-    /// TODO
-    #[allow(clippy::mut_from_ref)] // We do our own borrow checking.
-    pub fn get_element<T>(&'_ self, id: NodeId) -> (&'_ mut T::RecipeTuple, BorrowDropper<'_>)
+    /// Gets the cell of the node associated with the given [`NodeId`] after
+    /// ensuring that it exists and is of type [`T`].
+    fn get_cell<T>(&self, id: NodeId) -> Result<&RecipeTupleCell<T::RecipeTuple>, NecsError>
     where
         T: NodeRef,
     {
-        let node_cell: &RecipeTupleCell<T::RecipeTuple> = unsafe {
-            // TODO: ensure a custom NodeId can't be created to avoid a mismatch.
+        let node_type = self
+            .nodes
+            .try_mini_type_of::<T>()
+            .ok_or(NecsError::NotRegistered(type_name::<T>()))?;
+        self.check(id)?;
+        if id.node_type != node_type {
+            return Err(NecsError::WrongNodeType {
+                id,
+                expected: type_name::<T>(),
+            });
+        }
+        unsafe {
+            // SAFETY: We just made sure the NodeId's type corresponds to T.
+            // The key is still reserved, so the node must have been taken.
             self.nodes
                 .get_unchecked::<T, _>(id.node_type, id.instance)
-                .unwrap()
+                .ok_or(NecsError::Taken(id))
+        }
+    }
+
+    /// Removes a [T::RecipeTuple] from the storage and frees its key so that
+    /// it may be reused, returning [`None`] if the node had been taken.
+    ///
+    /// Fails if the node does not exist or is currently borrowed.
+    pub fn despawn<T>(&mut self, id: NodeId) -> Result<Option<T::RecipeTuple>, NecsError>
+    where
+        T: NodeRef,
+    {
+        let recipe_tuple = match self.take::<T>(id) {
+            Ok(recipe_tuple) => Some(recipe_tuple),
+            Err(NecsError::Taken(_)) => None,
+            Err(e) => return Err(e),
         };
+        self.key_factory.remove(id.instance);
+        Ok(recipe_tuple)
+    }
+
+    /// Removes a [T::RecipeTuple] from the storage while keeping its key
+    /// reserved, so that it can be put back using [`Self::reinsert`].
+    ///
+    /// Fails if the node does not exist, was already taken or is currently
+    /// borrowed.
+    pub fn take<T>(&mut self, id: NodeId) -> Result<T::RecipeTuple, NecsError>
+    where
+        T: NodeRef,
+    {
+        if self.get_cell::<T>(id)?.borrowed.load(Acquire) {
+            return Err(NecsError::AlreadyBorrowed(id));
+        }
+        let node_cell = self
+            .nodes
+            .remove::<T, _>(id.instance)
+            .ok_or(NecsError::Taken(id))?;
+        Ok(node_cell.recipe_tuple.into_inner())
+    }
+
+    /// Changes the type of a node taken with [`Self::take`] from [`From`] to
+    /// [`To`], returning its new [`NodeId`] under which it can be reinserted.
+    ///
+    /// Fails if the node does not exist, is not of type [`From`] or was not
+    /// taken.
+    pub fn retype<From, To>(&mut self, id: NodeId) -> Result<NodeId, NecsError>
+    where
+        From: NodeRef,
+        To: NodeRef,
+    {
+        match self.get_cell::<From>(id) {
+            Err(NecsError::Taken(_)) => {}
+            Ok(_) => return Err(NecsError::NotTaken(id)),
+            Err(e) => return Err(e),
+        }
+        let node_type = self
+            .nodes
+            .try_mini_type_of::<To>()
+            .ok_or(NecsError::NotRegistered(type_name::<To>()))?;
+        self.key_factory[id.instance] = node_type;
+        Ok(NodeId {
+            node_type,
+            instance: id.instance,
+        })
+    }
+
+    /// Puts a [T::RecipeTuple] previously removed with [`Self::take`] back
+    /// under the same [`NodeId`].
+    ///
+    /// Fails if the node was despawned in the meantime, is not of type [`T`],
+    /// or was not taken.
+    pub fn reinsert<T>(&mut self, id: NodeId, node: T::RecipeTuple) -> Result<(), NecsError>
+    where
+        T: NodeRef,
+    {
+        match self.get_cell::<T>(id) {
+            Err(NecsError::Taken(_)) => {}
+            Ok(_) => return Err(NecsError::NotTaken(id)),
+            Err(e) => return Err(e),
+        }
+        unsafe {
+            // SAFETY: get_cell() made sure the NodeId's type corresponds to T.
+            self.nodes.insert_unchecked::<T, _>(
+                id.node_type,
+                id.instance,
+                RecipeTupleCell {
+                    recipe_tuple: SyncUnsafeCell::new(node),
+                    borrowed: AtomicBool::new(false),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Borrows the [T::RecipeTuple] of the node associated with the given
+    /// [`NodeId`] until the returned [`BorrowDropper`] is dropped.
+    ///
+    /// # Panics
+    /// See [`Self::try_get_element`] for the conditions under which this
+    /// fails.
+    #[allow(clippy::mut_from_ref)] // We do our own borrow checking.
+    pub fn get_element<T>(&'_ self, id: NodeId) -> (&'_ mut T::RecipeTuple, BorrowDropper<'_>)
+    where
+        T: NodeRef,
+    {
+        self.try_get_element::<T>(id)
+            .unwrap_or_else(|e| access_failed(e))
+    }
+
+    /// Borrows the [T::RecipeTuple] of the node associated with the given
+    /// [`NodeId`] until the returned [`BorrowDropper`] is dropped.
+    ///
+    /// Fails if [`T`] is not registered, the node does not exist, is not of
+    /// type [`T`] or is already borrowed.
+    #[allow(clippy::mut_from_ref)] // We do our own borrow checking.
+    pub fn try_get_element<T>(
+        &'_ self,
+        id: NodeId,
+    ) -> Result<(&'_ mut T::RecipeTuple, BorrowDropper<'_>), NecsError>
+    where
+        T: NodeRef,
+    {
+        let node_cell = self.get_cell::<T>(id)?;
         match node_cell
             .borrowed
             .compare_exchange(false, true, Acquire, Relaxed)
         {
-            Ok(_) => (
+            Ok(_) => Ok((
                 unsafe { node_cell.recipe_tuple.get().as_mut_unchecked() },
                 BorrowDropper::new(&node_cell.borrowed),
-            ),
-            Err(_) => panic!("the same node should not be borrowed multiple times at once"),
+            )),
+            Err(_) => Err(NecsError::AlreadyBorrowed(id)),
         }
     }
 
+    /// Iterates over the ids of every node of type [`T`], yielding nothing if
+    /// [`T`] is not registered.
     pub fn get_ids<T: NodeRef>(&self) -> impl ExactSizeIterator<Item = NodeId> {
-        let node_type = self.nodes.mini_type_of::<T>();
+        // If T is not registered there are no keys, so the placeholder is never used.
+        let node_type = self
+            .nodes
+            .try_mini_type_of::<T>()
+            .unwrap_or(MiniTypeId::MAX);
         let keys = self.nodes.keys::<T, _>();
         keys.map(move |node_key: &ItemKey| NodeId {
             node_type,
@@ -112,6 +488,25 @@ impl NodeStorage {
         })
     }
 
+    /// Iterates over every node of type [`T`] along with its id in a single
+    /// pass, without borrowing them.
+    pub fn iter_cells<T: NodeRef>(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (NodeId, &RecipeTupleCell<T::RecipeTuple>)> {
+        // If T is not registered there are no nodes, so the placeholder is never used.
+        let node_type = self
+            .nodes
+            .try_mini_type_of::<T>()
+            .unwrap_or(MiniTypeId::MAX);
+        self.nodes.iter::<T, _>().map(move |(&key, node_cell)| {
+            let id = NodeId {
+                node_type,
+                instance: key,
+            };
+            (id, node_cell)
+        })
+    }
+
     pub unsafe fn get_node_cells_unchecked<T: NodeRef>(
         &self,
     ) -> impl ExactSizeIterator<Item = (&mut T::RecipeTuple, BorrowDropper<'_>)> {
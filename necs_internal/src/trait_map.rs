@@ -1,16 +1,17 @@
 use crate::node::{Node, NodeId};
 use crate::storage::{MiniTypeId, Storage};
-use crate::{NodeRef, NodeTrait};
+use crate::{NecsError, NodeRef, NodeTrait};
 use rustc_hash::FxHashMap as HashMap;
 use std::any::{Any, TypeId, type_name};
 use std::fmt::{Debug, Formatter};
 use std::mem::transmute;
 
+/// Builds a boxed trait object (itself boxed as [`Any`]) for a node.
+type TraitObjFactory =
+    Box<dyn Fn(&Storage, NodeId) -> Result<Box<dyn Any>, NecsError> + Send + Sync>;
+
 pub struct TraitMap {
-    map: HashMap<
-        TypeId,
-        HashMap<MiniTypeId, Box<dyn Fn(&Storage, NodeId) -> Box<dyn Any> + Send + Sync>>,
-    >,
+    map: HashMap<TypeId, HashMap<MiniTypeId, TraitObjFactory>>,
     trait_names: HashMap<TypeId, &'static str>,
     node_names: HashMap<MiniTypeId, &'static str>,
 }
@@ -23,18 +24,18 @@ impl Debug for TraitMap {
         }
         write!(f, "{{")?;
         if f.alternate() {
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         for (i, trait_type_key) in trait_type_keys.enumerate() {
             if i > 0 {
                 if f.alternate() {
-                    write!(f, ",\n")?;
+                    writeln!(f, ",")?;
                 } else {
                     write!(f, ", ")?;
                 }
             }
             let trait_name = self.trait_names.get(trait_type_key).unwrap();
-            let registered_nodes = self.map[&trait_type_key]
+            let registered_nodes = self.map[trait_type_key]
                 .keys()
                 .map(|x| self.node_names.get(x).unwrap());
             write!(f, "    {}: [", trait_name)?;
@@ -58,7 +59,7 @@ impl Debug for TraitMap {
             }
         }
         if f.alternate() {
-            write!(f, ",\n")?;
+            writeln!(f, ",")?;
         }
         write!(f, "}}")
     }
@@ -84,11 +85,11 @@ impl TraitMap {
         let closure = move |storage: &Storage, id: NodeId| {
             // TODO: ensure get_node() casts back to the proper lifetime.
             let storage: &'static Storage = unsafe { transmute(storage) };
-            let (recipe_tuple, borrow_dropper) = storage.nodes.get_element::<T>(id);
+            let (recipe_tuple, borrow_dropper) = storage.nodes.try_get_element::<T>(id)?;
             let node =
                 unsafe { T::__build_from_storage(recipe_tuple, borrow_dropper, storage, id) };
             let trait_obj: Box<Trait> = to_trait_obj(node);
-            Box::new(trait_obj) as Box<dyn Any>
+            Ok(Box::new(trait_obj) as Box<dyn Any>)
         };
 
         self.map
@@ -102,27 +103,87 @@ impl TraitMap {
         self.node_names.entry(node_type).or_insert(type_name::<T>());
     }
 
+    /// Removes the given node type from every trait it was registered for,
+    /// dropping traits that no longer have any node type registered.
+    pub fn unregister(&mut self, node_type: MiniTypeId) {
+        self.map.retain(|trait_type, type_map| {
+            type_map.remove(&node_type);
+            if type_map.is_empty() {
+                self.trait_names.remove(trait_type);
+            }
+            !type_map.is_empty()
+        });
+        self.node_names.remove(&node_type);
+    }
+
+    /// Returns whether any node type was registered for `Trait`.
+    pub fn is_registered<Trait: ?Sized + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<Trait>())
+    }
+
+    /// Returns whether the given node type was registered for `Trait`.
+    pub fn implements<Trait: ?Sized + 'static>(&self, node_type: MiniTypeId) -> bool {
+        self.map
+            .get(&TypeId::of::<Trait>())
+            .is_some_and(|type_map| type_map.contains_key(&node_type))
+    }
+
     pub fn get_node<Trait>(&self, storage: &Storage, id: NodeId) -> Box<Trait>
     where
         Trait: 'static + ?Sized,
     {
-        let type_map = self
+        self.try_get_node::<Trait>(storage, id)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_node<Trait>(
+        &self,
+        storage: &Storage,
+        id: NodeId,
+    ) -> Result<Box<Trait>, NecsError>
+    where
+        Trait: 'static + ?Sized,
+    {
+        storage.nodes.check(id)?;
+
+        let factory = self
             .map
             .get(&TypeId::of::<Trait>())
-            .unwrap_or_else(|| panic!("trait {} not registered", type_name::<Trait>()));
-
-        let factory = type_map.get(&id.node_type).unwrap_or_else(|| {
-            panic!(
-                "type {:?} not registered for Trait {}",
-                id.node_type,
-                type_name::<Trait>()
-            )
-        });
+            .and_then(|type_map| type_map.get(&id.node_type))
+            .ok_or(NecsError::TraitNotImplemented {
+                id,
+                trait_name: type_name::<Trait>(),
+            })?;
 
-        let trait_obj = factory(storage, id);
+        downcast_trait_obj(factory(storage, id)?)
+    }
 
-        *trait_obj
-            .downcast::<Box<Trait>>()
-            .expect("Failed to downcast the node to the expected trait object")
+    /// Iterates over the node types registered for `Trait`, along with a
+    /// function getting a node of that type as `Trait`.
+    pub fn implementors<Trait>(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            MiniTypeId,
+            impl Fn(&Storage, NodeId) -> Result<Box<Trait>, NecsError> + '_,
+        ),
+    >
+    where
+        Trait: 'static + ?Sized,
+    {
+        let type_map = self.map.get(&TypeId::of::<Trait>());
+        type_map.into_iter().flatten().map(|(&node_type, factory)| {
+            let get_node =
+                |storage: &Storage, id: NodeId| downcast_trait_obj(factory(storage, id)?);
+            (node_type, get_node)
+        })
     }
 }
+
+fn downcast_trait_obj<Trait: 'static + ?Sized>(
+    trait_obj: Box<dyn Any>,
+) -> Result<Box<Trait>, NecsError> {
+    Ok(*trait_obj
+        .downcast::<Box<Trait>>()
+        .expect("Failed to downcast the node to the expected trait object"))
+}
@@ -0,0 +1,212 @@
+use crate::{ItemKey, NodeId, NodeRef, NodeTrait, Relations, World};
+use std::collections::VecDeque;
+
+/// An iterator over [`NodeId`]s in the tree of a [`World`], which can be
+/// narrowed down to nodes of a concrete type or implementing a trait.
+pub trait TreeIterator<'world>: Iterator<Item = NodeId> + Sized {
+    /// Returns the [`World`] being traversed.
+    fn world(&self) -> &'world World;
+
+    /// Only yields nodes of type [T].
+    fn of_type<T: NodeRef>(self) -> impl Iterator<Item = NodeId> + 'world
+    where
+        Self: 'world,
+    {
+        let node_type = self.world().storage.nodes.try_mini_type_of::<T>();
+        self.filter(move |id| Some(id.node_type) == node_type)
+    }
+
+    /// Only yields active nodes, see [`World::is_active`].
+    fn active(self) -> impl Iterator<Item = NodeId> + 'world
+    where
+        Self: 'world,
+    {
+        let world = self.world();
+        self.filter(move |&id| world.is_active(id))
+    }
+
+    /// Only yields nodes whose type was registered for `Trait` with
+    /// [`World::register_trait`].
+    fn implementing<Trait>(self) -> impl Iterator<Item = NodeId> + 'world
+    where
+        Self: 'world,
+        Trait: NodeTrait + ?Sized + 'static,
+    {
+        let world = self.world();
+        self.filter(move |id| world.trait_map.implements::<Trait>(id.node_type))
+    }
+}
+
+/// Depth-first iterator over the descendants of a node, see
+/// [`World::descendants`].
+#[derive(Debug, Clone)]
+pub struct Descendants<'world> {
+    world: &'world World,
+    // Nodes left to visit, the next one last.
+    stack: Vec<ItemKey>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let key = self.stack.pop()?;
+        self.stack
+            .extend(self.world.child_keys(key).iter().rev().copied());
+        Some(self.world.id_of_relative(key))
+    }
+}
+
+impl<'world> TreeIterator<'world> for Descendants<'world> {
+    fn world(&self) -> &'world World {
+        self.world
+    }
+}
+
+/// Breadth-first iterator over the descendants of a node, see
+/// [`World::descendants_bfs`].
+#[derive(Debug, Clone)]
+pub struct DescendantsBfs<'world> {
+    world: &'world World,
+    // Nodes left to visit, the next one first.
+    queue: VecDeque<ItemKey>,
+}
+
+impl Iterator for DescendantsBfs<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let key = self.queue.pop_front()?;
+        self.queue.extend(self.world.child_keys(key));
+        Some(self.world.id_of_relative(key))
+    }
+}
+
+impl<'world> TreeIterator<'world> for DescendantsBfs<'world> {
+    fn world(&self) -> &'world World {
+        self.world
+    }
+}
+
+/// Iterator over the ancestors of a node, starting with its parent, see
+/// [`World::ancestors`].
+#[derive(Debug, Clone)]
+pub struct Ancestors<'world> {
+    world: &'world World,
+    next: Option<ItemKey>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let key = self.next?;
+        self.next = self.world.community.get(&key).and_then(Relations::parent);
+        Some(self.world.id_of_relative(key))
+    }
+}
+
+impl<'world> TreeIterator<'world> for Ancestors<'world> {
+    fn world(&self) -> &'world World {
+        self.world
+    }
+}
+
+/// Iterator over the other children of a node's parent, see
+/// [`World::siblings`].
+#[derive(Debug, Clone)]
+pub struct Siblings<'world> {
+    world: &'world World,
+    siblings: std::slice::Iter<'world, ItemKey>,
+    // The node whose siblings are iterated over, which is skipped.
+    skip: ItemKey,
+}
+
+impl Iterator for Siblings<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let key = *self.siblings.find(|&&key| key != self.skip)?;
+        Some(self.world.id_of_relative(key))
+    }
+}
+
+impl<'world> TreeIterator<'world> for Siblings<'world> {
+    fn world(&self) -> &'world World {
+        self.world
+    }
+}
+
+impl World {
+    /// Iterates depth-first over every descendant of the given node, visiting
+    /// children in order. The node itself is not included.
+    ///
+    /// Yields nothing if the node does not exist.
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_> {
+        Descendants {
+            world: self,
+            stack: self.checked_child_keys(id).iter().rev().copied().collect(),
+        }
+    }
+
+    /// Like [`descendants`](World::descendants), but breadth-first, so nodes
+    /// closer to the given node come first.
+    pub fn descendants_bfs(&self, id: NodeId) -> DescendantsBfs<'_> {
+        DescendantsBfs {
+            world: self,
+            queue: self.checked_child_keys(id).iter().copied().collect(),
+        }
+    }
+
+    /// Iterates over the parent of the given node, then its parent, and so on
+    /// up to the root. The node itself is not included.
+    ///
+    /// Yields nothing if the node does not exist.
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_> {
+        let next = self
+            .community
+            .get(&id.instance)
+            .filter(|_| self.contains(id))
+            .and_then(Relations::parent);
+        Ancestors { world: self, next }
+    }
+
+    /// Iterates over the other children of the given node's parent, in order.
+    ///
+    /// Root nodes and nodes that don't exist have no siblings.
+    pub fn siblings(&self, id: NodeId) -> Siblings<'_> {
+        let siblings = self
+            .ancestors(id)
+            .next()
+            .map_or(&[][..], |parent| self.child_keys(parent.instance));
+        Siblings {
+            world: self,
+            siblings: siblings.iter(),
+            skip: id.instance,
+        }
+    }
+
+    /// Returns the keys of the children of the given node, or nothing if it
+    /// does not exist.
+    fn checked_child_keys(&self, id: NodeId) -> &[ItemKey] {
+        if self.contains(id) {
+            self.child_keys(id.instance)
+        } else {
+            &[]
+        }
+    }
+
+    pub(crate) fn child_keys(&self, key: ItemKey) -> &[ItemKey] {
+        self.community
+            .get(&key)
+            .map_or(&[], |relations| relations.children())
+    }
+
+    /// Returns the [`NodeId`] of a node found through [`Relations`].
+    pub(crate) fn id_of_relative(&self, key: ItemKey) -> NodeId {
+        self.storage
+            .nodes
+            .id_of(key)
+            .expect("relatives should always be alive")
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{ItemKey, NodeId, World};
+
+/// An inconsistency in the hierarchy found by [`World::validate`].
+///
+/// Keys of nodes that no longer exist are reported as [`ItemKey`]s.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HierarchyIssue {
+    /// [`World::community`] has an entry for a node that does not exist.
+    DeadRelations(ItemKey),
+    /// The node has no entry in [`World::community`].
+    MissingRelations(NodeId),
+    /// The parent of the node does not exist.
+    DeadParent { id: NodeId, parent: ItemKey },
+    /// A child of the node does not exist.
+    DeadChild { id: NodeId, child: ItemKey },
+    /// The node has a parent that does not list it as a child.
+    NotAChildOfParent { id: NodeId, parent: NodeId },
+    /// The node lists a child whose parent is another node, or none.
+    ChildOfAnotherParent { id: NodeId, child: NodeId },
+    /// The node lists the same child more than once.
+    DuplicateChild { id: NodeId, child: NodeId },
+    /// The node is its own ancestor.
+    Cycle(NodeId),
+}
+
+/// Every [`HierarchyIssue`] found by [`World::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<HierarchyIssue>,
+}
+
+impl ValidationReport {
+    /// Returns whether no issue was found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl World {
+    /// Checks that the [`Relations`](crate::Relations) of every node are
+    /// consistent with each other and with the nodes in storage, which can
+    /// only break by modifying them with
+    /// [`edit_community`](World::edit_community).
+    ///
+    /// This walks the whole hierarchy, so it is meant for debugging and
+    /// tests.
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues = Vec::new();
+        for id in self.storage.nodes.ids() {
+            if !self.community.contains_key(&id.instance) {
+                issues.push(HierarchyIssue::MissingRelations(id));
+            }
+        }
+        for (&key, relations) in &self.community {
+            let Some(id) = self.storage.nodes.id_of(key) else {
+                issues.push(HierarchyIssue::DeadRelations(key));
+                continue;
+            };
+            if let Some(parent_key) = relations.parent() {
+                match self.storage.nodes.id_of(parent_key) {
+                    None => issues.push(HierarchyIssue::DeadParent {
+                        id,
+                        parent: parent_key,
+                    }),
+                    Some(parent) => {
+                        let listed = self
+                            .community
+                            .get(&parent_key)
+                            .is_some_and(|parent| parent.children().contains(&key));
+                        if !listed {
+                            issues.push(HierarchyIssue::NotAChildOfParent { id, parent });
+                        }
+                    }
+                }
+            }
+            for (i, &child_key) in relations.children().iter().enumerate() {
+                let Some(child) = self.storage.nodes.id_of(child_key) else {
+                    issues.push(HierarchyIssue::DeadChild {
+                        id,
+                        child: child_key,
+                    });
+                    continue;
+                };
+                if relations.children()[..i].contains(&child_key) {
+                    issues.push(HierarchyIssue::DuplicateChild { id, child });
+                }
+                let child_parent = self
+                    .community
+                    .get(&child_key)
+                    .and_then(|child| child.parent());
+                if child_parent != Some(key) {
+                    issues.push(HierarchyIssue::ChildOfAnotherParent { id, child });
+                }
+            }
+            if self.is_on_cycle(key) {
+                issues.push(HierarchyIssue::Cycle(id));
+            }
+        }
+        ValidationReport { issues }
+    }
+
+    /// Returns whether following the parents of the node leads back to it.
+    fn is_on_cycle(&self, key: ItemKey) -> bool {
+        let mut current = key;
+        // A path without cycles can't be longer than the number of nodes.
+        for _ in 0..self.community.len() {
+            match self
+                .community
+                .get(&current)
+                .and_then(|relations| relations.parent())
+            {
+                Some(parent) if parent == key => return true,
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+}
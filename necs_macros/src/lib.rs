@@ -188,6 +188,92 @@ impl ToTokens for GeneratedNodeBuilder {
             _ => unreachable!("struct fields should not be unnamed"),
         };
 
+        // Generate the body of __reinsert_into_storage().
+        let reinsert_body = match &self.fields {
+            Fields::Named(fields) => {
+                let mut tuple_fields = Vec::new();
+                let mut insertions = Vec::new();
+                for field in fields.named.iter() {
+                    let field_name = &field.ident;
+                    if field.attrs.iter().any(|attr| attr.path().is_ident("ext")) {
+                        insertions.push(quote! {
+                            storage.components.insert(id.instance, self.#field_name);
+                        });
+                    } else {
+                        tuple_fields.push(quote! { self.#field_name });
+                    }
+                }
+
+                quote! {
+                    storage.nodes.reinsert::<Self::AsNodeRef>(id, (#(#tuple_fields,)*))?;
+                    #(#insertions)*
+                    Ok(())
+                }
+            }
+            Fields::Unit => quote! {
+                storage.nodes.reinsert::<Self::AsNodeRef>(id, ())
+            },
+            _ => unreachable!("struct fields should not be unnamed"),
+        };
+
+        // Generate the body of __move_batch_to_storage(), which resolves the type of
+        // the node and of every #[ext] field once up front.
+        let batch_body = match &self.fields {
+            Fields::Named(fields) => {
+                let ext_fields = fields
+                    .named
+                    .iter()
+                    .filter(|field| field.attrs.iter().any(|attr| attr.path().is_ident("ext")));
+
+                let mut component_types = Vec::new();
+                let mut insertions = Vec::new();
+                for (i, field) in ext_fields.enumerate() {
+                    let field_name = &field.ident;
+                    let field_ty = &field.ty;
+                    let i = syn::Index::from(i);
+                    component_types.push(quote! {
+                        storage.components.mini_type_of::<#field_ty>()
+                    });
+                    insertions.push(quote! {
+                        storage.components.insert_with_id(
+                            &unsafe { ::necs::ComponentId::new(component_types.#i, node_id.instance) },
+                            node.#field_name,
+                        );
+                    });
+                }
+
+                let tuple_fields = fields.named.iter().filter_map(|field| {
+                    let field_name = &field.ident;
+                    let has_ext = field.attrs.iter().any(|attr| attr.path().is_ident("ext"));
+
+                    if has_ext {
+                        None
+                    } else {
+                        Some(quote! { node.#field_name })
+                    }
+                });
+
+                quote! {
+                    let component_types = (#(#component_types,)*);
+                    // Safety: node_type and component_types were looked up from the exact types
+                    // they are used with.
+                    nodes
+                        .map(|node| {
+                            let node_id = unsafe { storage.nodes.spawn_as::<Self::AsNodeRef>(node_type, (#(#tuple_fields,)*)) };
+                            #(#insertions)*
+                            node_id
+                        })
+                        .collect()
+                }
+            }
+            Fields::Unit => quote! {
+                nodes
+                    .map(|_| unsafe { storage.nodes.spawn_as::<Self::AsNodeRef>(node_type, ()) })
+                    .collect()
+            },
+            _ => unreachable!("struct fields should not be unnamed"),
+        };
+
         // Add the <'static> lifetime annotation if the struct has at least one field.
         let as_node_ref = match &self.fields {
             Fields::Named(fields) if !fields.named.is_empty() => {
@@ -207,6 +293,20 @@ impl ToTokens for GeneratedNodeBuilder {
                 fn __move_to_storage(self, storage: &mut ::necs::storage::Storage) -> ::necs::NodeId {
                     #field_assignments
                 }
+
+                fn __reinsert_into_storage(self, storage: &mut ::necs::storage::Storage, id: ::necs::NodeId) -> ::std::result::Result<(), ::necs::NecsError> {
+                    #reinsert_body
+                }
+
+                fn __move_batch_to_storage<I>(nodes: I, storage: &mut ::necs::storage::Storage) -> ::std::vec::Vec<::necs::NodeId>
+                where
+                    I: ::std::iter::IntoIterator<Item = Self>,
+                {
+                    let nodes = nodes.into_iter();
+                    <Self::AsNodeRef as ::necs::NodeRef>::__reserve(storage, nodes.size_hint().0);
+                    let node_type = storage.nodes.mini_type_of::<Self::AsNodeRef>();
+                    #batch_body
+                }
             }
         }
         .to_tokens(tokens);
@@ -290,7 +390,7 @@ impl Parse for GeneratedNodeRef {
             }
         }
 
-        let recipe_ty: Type = parse_quote!((#(#tuple_types),*));
+        let recipe_ty: Type = parse_quote!((#(#tuple_types,)*));
         let recipe_tuple = match recipe_ty {
             Type::Tuple(tup) => tup,
             _ => return Err(syn::Error::new_spanned(recipe_ty, "expected tuple type")),
@@ -350,6 +450,8 @@ impl ToTokens for GeneratedNodeRef {
 
         let mut field_extractions = Vec::new();
         let mut component_registrations = Vec::new();
+        let mut component_reservations = Vec::new();
+        let mut component_removals = Vec::new();
         let generic_idents = only_generic_idents(generics);
         let mut world_and_generics = generics.clone();
         let mut world_and_generic_idents = generic_idents.clone();
@@ -359,7 +461,6 @@ impl ToTokens for GeneratedNodeRef {
             world_and_generic_idents = with_lifetime(world_and_generic_idents, "world");
             static_and_generic_idents = with_lifetime(static_and_generic_idents, "static");
         }
-        let mut mini_type_id_tuple = Vec::new();
 
         let ext_fields = fields.iter().filter(|field| field.is_ext);
         let local_fields = fields.iter().filter(|field| !field.is_ext);
@@ -367,13 +468,11 @@ impl ToTokens for GeneratedNodeRef {
         for (i, field) in ext_fields.enumerate() {
             let name = &field.ident;
 
-            let i = syn::Index::from(i);
-
             // For #ext fields, access them using the node's instance id.
             if let Type::Reference(type_ref) = &field.ty {
                 let inner_type = &type_ref.elem;
                 field_extractions.push(quote! {
-                        let #name = unsafe { storage.components.get_element_unchecked(&::necs::ComponentId::<#inner_type>::new(mini_type_ids.#i, id.instance)) };
+                        let #name = unsafe { storage.components.get_element_unchecked(&::necs::ComponentId::<#inner_type>::new(component_types[#i], id.instance)) };
                     });
             }
 
@@ -383,9 +482,13 @@ impl ToTokens for GeneratedNodeRef {
                 component_registrations.push(quote! {
                     storage.components.register::<#inner_type>()
                 });
+                component_reservations.push(quote! {
+                    storage.components.reserve::<#inner_type>(additional);
+                });
+                component_removals.push(quote! {
+                    _ = storage.components.remove::<#inner_type>(id.instance);
+                });
             }
-
-            mini_type_id_tuple.push(quote! { ::necs::storage::MiniTypeId });
         }
 
         for (i, field) in local_fields.enumerate() {
@@ -399,6 +502,79 @@ impl ToTokens for GeneratedNodeRef {
 
         let field_names = fields.iter().map(|f| &f.ident);
 
+        // Generate the body of __take_from_storage(), moving every field into locals of
+        // the same name before assembling the builder.
+        let builder_ident = format_ident!("{}Builder", ident);
+        let local_field_names = fields
+            .iter()
+            .filter(|field| !field.is_ext)
+            .map(|f| &f.ident);
+        let ext_takes = fields.iter().filter(|field| field.is_ext).map(|field| {
+            let name = &field.ident;
+            let Type::Reference(type_ref) = &field.ty else {
+                unreachable!("node fields should be references")
+            };
+            let inner_type = &type_ref.elem;
+            quote! {
+                let #name = storage
+                    .components
+                    .remove::<#inner_type>(id.instance)
+                    .expect("#[ext] field should be present on every stored node");
+            }
+        });
+        // Generate the body of CloneNode::__clone_builder(), along with bounds ensuring
+        // every field is Clone. The bounds are higher-ranked so that
+        // unsatisfiable bounds on concrete types simply disable the impl rather
+        // than causing an error.
+        let mut clone_bounds = Vec::new();
+        let mut field_clones = Vec::new();
+        for (i, field) in fields.iter().filter(|field| !field.is_ext).enumerate() {
+            let name = &field.ident;
+            let i = syn::Index::from(i);
+            field_clones.push(quote! {
+                let #name = ::std::clone::Clone::clone(&recipe_tuple.#i);
+            });
+        }
+        for field in fields {
+            let name = &field.ident;
+            let Type::Reference(type_ref) = &field.ty else {
+                unreachable!("node fields should be references")
+            };
+            let inner_type = &type_ref.elem;
+            clone_bounds.push(quote! {
+                for<'__necs> #inner_type: ::std::clone::Clone
+            });
+            if field.is_ext {
+                field_clones.push(quote! {
+                    let #name = ::std::clone::Clone::clone(&*storage.components.get_element::<#inner_type>(&id.instance));
+                });
+            }
+        }
+        let cloned_field_names = fields.iter().map(|f| &f.ident);
+        // Nodes without local fields don't need their recipe tuple, only the borrow.
+        let recipe_tuple_binding = if fields.iter().any(|field| !field.is_ext) {
+            quote!(recipe_tuple)
+        } else {
+            quote!(_)
+        };
+        let clone_body = quote! {
+            // Holding the borrow also guards the #[ext] fields while they are cloned.
+            let (#recipe_tuple_binding, _borrowed) = storage.nodes.try_get_element::<Self>(id)?;
+            #(#field_clones)*
+            Ok(#builder_ident {
+                #(#cloned_field_names,)*
+            })
+        };
+
+        let all_field_names = fields.iter().map(|f| &f.ident);
+        let take_body = quote! {
+            let (#(#local_field_names,)*) = storage.nodes.take::<Self>(id)?;
+            #(#ext_takes)*
+            Ok(#builder_ident {
+                #(#all_field_names,)*
+            })
+        };
+
         // Generate match arms for Node::get implementation
         let get_match_arms = fields.iter().map(|field| {
             let name = &field.ident;
@@ -416,8 +592,6 @@ impl ToTokens for GeneratedNodeRef {
                 #(#struct_fields)*
             }
 
-            static MINI_TYPE_IDS: std::sync::OnceLock<(#( #mini_type_id_tuple, )*)> = std::sync::OnceLock::new();
-
             #[doc(hidden)]
             impl #world_and_generics ::necs::NodeTrait for #ident #world_and_generic_idents {
                 fn get(&mut self, field_name: &str) -> &mut dyn ::necs::Field {
@@ -432,10 +606,11 @@ impl ToTokens for GeneratedNodeRef {
             impl #generics ::necs::NodeRef for #ident #static_and_generic_idents {
                 type Instance<'world> = #ident #world_and_generic_idents;
                 type RecipeTuple = #recipe_tuple;
+                type Builder = #builder_ident #generic_idents;
 
                 unsafe fn __build_from_storage<'world>(recipe_tuple: &'world mut Self::RecipeTuple, borrowed: ::necs::BorrowDropper<'world>, storage: &'world ::necs::storage::Storage, id: ::necs::NodeId) -> #ident #world_and_generic_idents {
                     // We were able to get recipe_tuple, so components should also be registered.
-                    let mini_type_ids = MINI_TYPE_IDS.get().unwrap();
+                    let component_types = storage.nodes.component_types(id.node_type);
                     #(#field_extractions)*
                     #ident {
                         #borrowed
@@ -444,11 +619,44 @@ impl ToTokens for GeneratedNodeRef {
                 }
 
                 fn __register_node(storage: &mut ::necs::storage::Storage) {
+                    // Register every #[ext] field with component storage.
+                    let component_types = ::std::vec![#( #component_registrations, )*];
+
                     // Register the node itself.
-                    _ = storage.nodes.register::<Self>();
+                    storage.nodes.register::<Self>(component_types);
+
+                    // Allow cloning this node without knowing its type if it implements CloneNode.
+                    // This can't be decided for generic nodes, which are registered through
+                    // World::register_clone instead.
+                    #[allow(unused_imports)]
+                    use ::necs::{ViaCloneNode as _, ViaNoCloneNode as _};
+                    (&&::necs::CloneProbe::<Self>::new()).__register_clone(storage);
+                }
 
-                    // Register every #[ext] field with component storage.
-                    _ = MINI_TYPE_IDS.set((#( #component_registrations, )*));
+                fn __reserve(storage: &mut ::necs::storage::Storage, additional: usize) {
+                    storage.nodes.reserve::<Self>(additional);
+                    #(#component_reservations)*
+                }
+
+                fn __take_from_storage(storage: &mut ::necs::storage::Storage, id: ::necs::NodeId) -> ::std::result::Result<Self::Builder, ::necs::NecsError> {
+                    #take_body
+                }
+
+                fn __remove_from_storage(storage: &mut ::necs::storage::Storage, id: ::necs::NodeId) -> ::std::result::Result<(), ::necs::NecsError> {
+                    // Only remove components once we know the node itself could be removed.
+                    _ = storage.nodes.despawn::<Self>(id)?;
+                    #(#component_removals)*
+                    Ok(())
+                }
+            }
+
+            #[doc(hidden)]
+            impl #generics ::necs::CloneNode for #ident #static_and_generic_idents
+            where
+                #(#clone_bounds,)*
+            {
+                fn __clone_builder(storage: &::necs::storage::Storage, id: ::necs::NodeId) -> ::std::result::Result<Self::Builder, ::necs::NecsError> {
+                    #clone_body
                 }
             }
         }.to_tokens(tokens);
@@ -1,5 +1,8 @@
 pub use necs_internal::World;
 #[doc(hidden)]
 pub use necs_internal::*;
-pub use necs_internal::{Node, NodeId, NodeTrait};
+pub use necs_internal::{
+    ComponentMut, DespawnPolicy, Event, EventPhase, Handles, HierarchyIssue, Lifecycle, NecsError,
+    Node, NodeId, NodeQuery, NodeTrait, ProcessMode, Query, TreeIterator, ValidationReport,
+};
 pub use necs_macros::node;
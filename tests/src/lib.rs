@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use necs::{node, Node, NodeTrait, World};
+    use necs::{
+        DespawnPolicy, Event, EventPhase, Handles, HierarchyIssue, Lifecycle, NecsError, Node,
+        NodeId, NodeQuery, NodeTrait, ProcessMode, Relations, TreeIterator, World, node,
+    };
 
     #[derive(Debug)]
     struct Useless;
@@ -20,13 +23,105 @@ mod tests {
     #[node]
     struct Baz;
 
+    #[node]
+    struct Wrapper<T: 'static + Send + Sync> {
+        value: T,
+    }
+
+    #[node]
+    struct Enemy {
+        health: u32,
+        #[ext]
+        position: (f32, f32),
+    }
+
+    #[node]
+    struct Corpse {
+        decay: f32,
+        #[ext]
+        position: (f32, f32),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Offset(f32);
+
+    #[derive(Debug, PartialEq)]
+    struct Position(f32);
+
+    #[node]
+    struct Spatial {
+        #[ext]
+        offset: Offset,
+        #[ext]
+        position: Position,
+    }
+
+    #[node]
+    struct Button {
+        clicks: u32,
+    }
+
+    #[derive(Default)]
+    struct Click {
+        path: Vec<(NodeId, EventPhase)>,
+    }
+
+    impl Handles<Click> for Button<'_> {
+        fn handle(&mut self, event: &mut Event<'_, Click>) {
+            *self.clicks += 1;
+            let step = (event.current(), event.phase());
+            event.path.push(step);
+        }
+    }
+
+    #[node]
+    struct Tracked {
+        tag: char,
+    }
+
+    thread_local! {
+        static LIFECYCLE_LOG: std::cell::RefCell<String> = Default::default();
+    }
+
+    impl Lifecycle for Tracked<'_> {
+        fn on_enter_tree(&mut self, _: &World, _: NodeId) {
+            LIFECYCLE_LOG.with_borrow_mut(|log| log.extend(['+', *self.tag]));
+        }
+
+        fn on_ready(&mut self, _: &World, _: NodeId) {
+            LIFECYCLE_LOG.with_borrow_mut(|log| log.extend(['!', *self.tag]));
+        }
+
+        fn on_exit_tree(&mut self, world: &World, id: NodeId) {
+            // The subtree is still intact when leaving the tree.
+            assert!(world.contains(id));
+            LIFECYCLE_LOG.with_borrow_mut(|log| log.extend(['-', *self.tag]));
+        }
+    }
+
     trait Process: NodeTrait {
         fn process(&self);
     }
 
     impl<T: Send + Sync> Process for Foo<'_, T> {
         fn process(&self) {
-            println!("{:?}", &self.y);
+            println!("{:?}", self.y);
+        }
+    }
+
+    trait Describe: NodeTrait {
+        fn describe(&self) -> String;
+    }
+
+    impl Describe for Enemy<'_> {
+        fn describe(&self) -> String {
+            format!("enemy {}", self.health)
+        }
+    }
+
+    impl Describe for Corpse<'_> {
+        fn describe(&self) -> String {
+            format!("corpse {}", self.decay)
         }
     }
 
@@ -70,6 +165,1202 @@ mod tests {
         }
     }
 
+    #[test]
+    fn despawn() {
+        let mut world = World::new();
+        world.register_node::<Foo<u32>>();
+
+        let node_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 2u32,
+        });
+
+        // A node can't be despawned while it is borrowed.
+        let node = world.get_node_resilient::<dyn Node>(node_id);
+        assert_eq!(
+            world.despawn_node(node_id),
+            Err(NecsError::AlreadyBorrowed(node_id))
+        );
+        drop(node);
+
+        world.despawn_node(node_id).unwrap();
+        assert_eq!(world.get_node_ids::<Foo<u32>>().len(), 0);
+        assert!(world.community().is_empty());
+        // Despawning twice is an error.
+        assert_eq!(
+            world.despawn_node(node_id),
+            Err(NecsError::StaleId(node_id))
+        );
+    }
+
+    #[test]
+    fn stale_ids() {
+        let mut world = World::new();
+        world.register_node::<Foo<u32>>();
+
+        let old_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 2u32,
+        });
+        world.despawn_node(old_id).unwrap();
+        assert!(!world.contains(old_id));
+
+        // The new node reuses the slot, but the old id must not refer to it.
+        let new_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 4,
+            z: 5,
+            bar: 6u32,
+        });
+        assert!(world.contains(new_id));
+        assert!(!world.contains(old_id));
+        assert_eq!(world.despawn_node(old_id), Err(NecsError::StaleId(old_id)));
+        assert_eq!(*world.get_node::<Foo<u32>>(new_id).y, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale id")]
+    fn get_stale_node() {
+        let mut world = World::new();
+        world.register_node::<Foo<u32>>();
+
+        let node_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 2u32,
+        });
+        world.despawn_node(node_id).unwrap();
+        world.get_node::<Foo<u32>>(node_id);
+    }
+
+    #[test]
+    fn fallible_access() {
+        let mut world = World::new();
+
+        let node_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 2u32,
+        });
+
+        assert!(matches!(
+            world.try_get_node::<Bar>(node_id),
+            Err(NecsError::NotRegistered(_))
+        ));
+        world.register_node::<Bar>();
+
+        assert!(matches!(
+            world.try_get_node::<Bar>(node_id),
+            Err(NecsError::WrongNodeType { .. })
+        ));
+        assert!(matches!(
+            world.try_get_node_resilient::<dyn Process>(node_id),
+            Err(NecsError::TraitNotImplemented { .. })
+        ));
+
+        let mut node = world.try_get_node_resilient::<dyn Node>(node_id).unwrap();
+        assert_eq!(
+            world.try_get_node::<Foo<u32>>(node_id).err(),
+            Some(NecsError::AlreadyBorrowed(node_id))
+        );
+        assert!(matches!(
+            node.get("bar").try_to::<i64>(),
+            Err(NecsError::WrongFieldType(_))
+        ));
+        assert_eq!(node.get("bar").try_to::<u32>(), Ok(&mut 2));
+    }
+
+    #[test]
+    fn spawn_batch() {
+        let mut world = World::new();
+        world.register_node::<Foo<u32>>();
+        world.register_node::<Baz>();
+        world.reserve::<Foo<u32>>(16);
+
+        let node_ids = world.spawn_batch((0..16).map(|i| FooBuilder {
+            x: Useless,
+            y: i,
+            z: 0,
+            bar: i as u32,
+        }));
+        assert_eq!(node_ids.len(), 16);
+        for (i, node_id) in node_ids.into_iter().enumerate() {
+            let node = world.get_node::<Foo<u32>>(node_id);
+            assert_eq!(*node.y, i as i32);
+            assert_eq!(*node.bar, i as u32);
+        }
+
+        assert_eq!(world.spawn_batch([BazBuilder, BazBuilder]).len(), 2);
+        assert_eq!(world.get_node_ids::<Baz>().len(), 2);
+        assert_eq!(world.community().len(), 18);
+    }
+
+    #[test]
+    fn single_field_node() {
+        // The recipe tuple of a node with a single non-#[ext] field is a 1-tuple.
+        #[node]
+        struct Label {
+            text: &'static str,
+            #[ext]
+            position: (f32, f32),
+        }
+
+        let mut world = World::new();
+        world.register_node::<Label>();
+        let node_id = world.spawn_node(LabelBuilder {
+            text: "label",
+            position: (1.0, 2.0),
+        });
+        let label = world.get_node::<Label>(node_id);
+        assert_eq!(*label.text, "label");
+        assert_eq!(*label.position, (1.0, 2.0));
+    }
+
+    #[test]
+    fn commands() {
+        let mut world = World::new();
+        world.register_node::<Foo<u32>>();
+        world.register_node::<Baz>();
+        let node_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 2u32,
+        });
+
+        // Commands can be recorded from multiple threads while nodes are borrowed.
+        let commands = world.commands();
+        let nodes = world.get_nodes::<Foo<u32>>();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| commands.spawn(BazBuilder));
+            }
+        });
+        commands.despawn(node_id);
+        commands.add(move |world| {
+            // Applied after the despawn, so this should fail.
+            world.despawn_node(node_id)
+        });
+        drop(nodes);
+
+        assert_eq!(world.get_node_ids::<Baz>().len(), 0);
+        assert_eq!(world.apply_commands(), Err(NecsError::StaleId(node_id)));
+        assert_eq!(world.get_node_ids::<Baz>().len(), 4);
+        assert!(!world.contains(node_id));
+        // Everything was applied, so there is nothing left to do.
+        assert_eq!(world.apply_commands(), Ok(()));
+    }
+
+    #[test]
+    fn take_and_reinsert() {
+        let mut world = World::new();
+        world.register_node::<Foo<u32>>();
+        world.register_node::<Baz>();
+        let node_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 2u32,
+        });
+
+        let mut builder = world.take_node::<Foo<u32>>(node_id).unwrap();
+        assert_eq!(builder.y, 3);
+        assert_eq!(builder.bar, 2);
+        assert!(world.contains(node_id));
+        assert_eq!(world.get_node_ids::<Foo<u32>>().len(), 0);
+        assert_eq!(
+            world.try_get_node::<Foo<u32>>(node_id).err(),
+            Some(NecsError::Taken(node_id))
+        );
+
+        // The builder can be processed on another thread.
+        let mut builder = std::thread::spawn(move || {
+            builder.bar += 40;
+            builder
+        })
+        .join()
+        .unwrap();
+        builder.y = 5;
+
+        world.reinsert_node(node_id, builder).unwrap();
+        let node = world.get_node::<Foo<u32>>(node_id);
+        assert_eq!((*node.y, *node.bar), (5, 42));
+        drop(node);
+        assert_eq!(
+            world.reinsert_node(node_id, BazBuilder),
+            Err(NecsError::WrongNodeType {
+                id: node_id,
+                expected: std::any::type_name::<Baz>(),
+            })
+        );
+
+        // Taken nodes can still be despawned.
+        world.take_node::<Foo<u32>>(node_id).unwrap();
+        world.despawn_node(node_id).unwrap();
+        assert!(!world.contains(node_id));
+    }
+
+    #[test]
+    fn clone_node() {
+        let mut world = World::new();
+        world.register_node::<Enemy>();
+        let node_id = world.spawn_node(EnemyBuilder {
+            health: 10,
+            position: (1.0, 2.0),
+        });
+
+        let clone_id = world.clone_node::<Enemy>(node_id).unwrap();
+        assert_ne!(clone_id, node_id);
+        *world.get_node::<Enemy>(clone_id).health = 5;
+        let node = world.get_node::<Enemy>(node_id);
+        let clone = world.get_node::<Enemy>(clone_id);
+        assert_eq!((*node.health, *node.position), (10, (1.0, 2.0)));
+        assert_eq!((*clone.health, *clone.position), (5, (1.0, 2.0)));
+        drop((node, clone));
+
+        let child_id = world.spawn_child(node_id, BazBuilder).unwrap();
+        let clone_id = world.clone_node_recursive::<Enemy>(node_id).unwrap();
+        assert_eq!(*world.get_node::<Enemy>(clone_id).health, 10);
+        assert_eq!(world.get_node_ids::<Enemy>().len(), 3);
+        let child_clones = world.children_of(clone_id).collect::<Vec<_>>();
+        assert_eq!(child_clones.len(), 1);
+        assert_ne!(child_clones[0], child_id);
+        assert_eq!(world.parent_of(child_clones[0]), Some(clone_id));
+    }
+
+    #[test]
+    fn clone_generic_node() {
+        let mut world = World::new();
+        let parent = world.spawn_node(EnemyBuilder {
+            health: 10,
+            position: (1.0, 2.0),
+        });
+        let child = world
+            .spawn_child(parent, WrapperBuilder { value: 'a' })
+            .unwrap();
+
+        // Generic node types can only be cloned without knowing their type once
+        // registered for it.
+        assert_eq!(
+            world.clone_node_recursive::<Enemy>(parent),
+            Err(NecsError::NotCloneable(child))
+        );
+        assert_eq!(world.get_node_ids::<Enemy>().len(), 1);
+        world.register_clone::<Wrapper<char>>();
+        let clone_id = world.clone_node_recursive::<Enemy>(parent).unwrap();
+        let child_clones: Vec<NodeId> = world.children_of(clone_id).collect();
+        assert_eq!(child_clones.len(), 1);
+        assert_eq!(*world.get_node::<Wrapper<char>>(child_clones[0]).value, 'a');
+
+        // Cloning a generic node directly needs no registration.
+        let single = world.spawn_node(WrapperBuilder { value: 7u8 });
+        let clone_id = world.clone_node::<Wrapper<u8>>(single).unwrap();
+        assert_eq!(*world.get_node::<Wrapper<u8>>(clone_id).value, 7);
+    }
+
+    #[test]
+    fn automatic_registration() {
+        let mut world = World::new();
+        assert_eq!(world.get_nodes::<Enemy>().len(), 0);
+        assert_eq!(world.get_node_ids::<Foo<u32>>().len(), 0);
+
+        // Registering in a different order than other tests must not matter.
+        let baz_id = world.spawn_node(BazBuilder);
+        let foo_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 3,
+            z: 2,
+            bar: 7u32,
+        });
+        let enemy_ids = world.spawn_batch([EnemyBuilder {
+            health: 10,
+            position: (1.0, 2.0),
+        }]);
+
+        assert_eq!(*world.get_node::<Foo<u32>>(foo_id).bar, 7);
+        assert_eq!(*world.get_node::<Enemy>(enemy_ids[0]).position, (1.0, 2.0));
+        let mut node = world.get_node_resilient::<dyn Node>(foo_id);
+        assert_eq!(*node.get("bar").to::<u32>(), 7);
+        drop(node);
+        world.get_node_resilient::<dyn Node>(baz_id);
+    }
+
+    #[test]
+    fn unregister_node() {
+        let mut world = World::new();
+        world.register_trait::<Foo<u32>, dyn Process, _>(|x| Box::new(x));
+        let node_ids = world.spawn_batch((0..4).map(|i| FooBuilder {
+            x: Useless,
+            y: i,
+            z: 0,
+            bar: 0u32,
+        }));
+        let taken_id = node_ids[3];
+        world.take_node::<Foo<u32>>(taken_id).unwrap();
+
+        let node = world.get_node_resilient::<dyn Node>(node_ids[0]);
+        assert_eq!(
+            world.unregister_node::<Foo<u32>>(),
+            Err(NecsError::AlreadyBorrowed(node_ids[0]))
+        );
+        drop(node);
+
+        world.unregister_node::<Foo<u32>>().unwrap();
+        assert_eq!(world.get_node_ids::<Foo<u32>>().len(), 0);
+        assert!(world.community().is_empty());
+        for node_id in node_ids {
+            assert!(!world.contains(node_id));
+            assert!(matches!(
+                world.try_get_node::<Foo<u32>>(node_id),
+                Err(NecsError::NotRegistered(_))
+            ));
+            assert_eq!(
+                world.try_get_node_resilient::<dyn Process>(node_id).err(),
+                Some(NecsError::StaleId(node_id))
+            );
+        }
+        assert!(matches!(
+            world.unregister_node::<Foo<u32>>(),
+            Err(NecsError::NotRegistered(_))
+        ));
+
+        // Other types registered afterwards must not be confused with Foo.
+        let enemy_id = world.spawn_node(EnemyBuilder {
+            health: 1,
+            position: (0.0, 0.0),
+        });
+        let foo_id = world.spawn_node(FooBuilder {
+            x: Useless,
+            y: 9,
+            z: 0,
+            bar: 0u32,
+        });
+        assert_eq!(*world.get_node::<Enemy>(enemy_id).health, 1);
+        assert_eq!(*world.get_node::<Foo<u32>>(foo_id).y, 9);
+        assert!(matches!(
+            world.try_get_node_resilient::<dyn Process>(foo_id),
+            Err(NecsError::TraitNotImplemented { .. })
+        ));
+    }
+
+    #[test]
+    fn morph_node() {
+        let mut world = World::new();
+        let enemy_id = world.spawn_node(EnemyBuilder {
+            health: 0,
+            position: (3.0, 4.0),
+        });
+
+        let corpse_id = world
+            .morph_node::<Enemy, Corpse>(enemy_id, |enemy| CorpseBuilder {
+                decay: enemy.health as f32,
+                position: enemy.position,
+            })
+            .unwrap();
+        assert_eq!(corpse_id.instance, enemy_id.instance);
+        assert!(world.contains(corpse_id));
+        assert!(!world.contains(enemy_id));
+        assert!(world.community().contains_key(&corpse_id.instance));
+        assert_eq!(world.get_node_ids::<Enemy>().len(), 0);
+
+        let corpse = world.get_node::<Corpse>(corpse_id);
+        assert_eq!((*corpse.decay, *corpse.position), (0.0, (3.0, 4.0)));
+        drop(corpse);
+
+        // The old id is rejected, but tells us where the node went.
+        let expected = Some(NecsError::TypeChanged {
+            id: enemy_id,
+            current: corpse_id,
+        });
+        assert_eq!(world.try_get_node::<Enemy>(enemy_id).err(), expected);
+        assert_eq!(
+            world.try_get_node_resilient::<dyn Node>(enemy_id).err(),
+            expected
+        );
+        assert_eq!(world.despawn_node(enemy_id).err(), expected);
+    }
+
+    #[test]
+    fn hierarchy() {
+        let mut world = World::new();
+        let root = world.spawn_node(BazBuilder);
+        let a = world.spawn_child(root, BarBuilder {}).unwrap();
+        let b = world.spawn_child(root, BarBuilder {}).unwrap();
+        assert_eq!(world.parent_of(a), Some(root));
+        assert_eq!(world.parent_of(root), None);
+        assert_eq!(world.children_of(root).collect::<Vec<_>>(), [a, b]);
+
+        world.set_parent(a, b).unwrap();
+        assert_eq!(world.parent_of(a), Some(b));
+        assert_eq!(world.children_of(root).collect::<Vec<_>>(), [b]);
+        assert_eq!(world.children_of(b).collect::<Vec<_>>(), [a]);
+
+        world.remove_parent(a).unwrap();
+        assert_eq!(world.parent_of(a), None);
+        assert_eq!(world.children_of(b).len(), 0);
+
+        world.commands().set_parent(a, root);
+        world.apply_commands().unwrap();
+        assert_eq!(world.children_of(root).collect::<Vec<_>>(), [b, a]);
+
+        // Despawned children are removed from their parent.
+        world.despawn_node(b).unwrap();
+        assert_eq!(world.children_of(root).collect::<Vec<_>>(), [a]);
+        assert_eq!(world.set_parent(a, b), Err(NecsError::StaleId(b)));
+        assert_eq!(
+            world.spawn_child(b, BazBuilder).err(),
+            Some(NecsError::StaleId(b))
+        );
+        assert_eq!(world.children_of(b).len(), 0);
+        assert_eq!(world.get_node_ids::<Baz>().len(), 1);
+    }
+
+    #[test]
+    fn despawn_recursive() {
+        let mut world = World::new();
+        let root = world.spawn_node(BazBuilder);
+        let a = world.spawn_child(root, BarBuilder {}).unwrap();
+        let b = world
+            .spawn_child(
+                a,
+                EnemyBuilder {
+                    health: 1,
+                    position: (0.0, 0.0),
+                },
+            )
+            .unwrap();
+        let c = world.spawn_child(a, BarBuilder {}).unwrap();
+        let d = world.spawn_child(c, BazBuilder).unwrap();
+
+        // Children of a despawned node become roots by default.
+        world.despawn_node(c).unwrap();
+        assert_eq!(world.parent_of(d), None);
+        assert_eq!(world.children_of(a).collect::<Vec<_>>(), [b]);
+
+        let node = world.get_node_resilient::<dyn Node>(b);
+        assert_eq!(
+            world.despawn_recursive(root),
+            Err(NecsError::AlreadyBorrowed(b))
+        );
+        drop(node);
+        assert!(world.contains(root) && world.contains(a));
+
+        world.despawn_recursive(root).unwrap();
+        for id in [root, a, b] {
+            assert!(!world.contains(id));
+        }
+        assert!(world.contains(d));
+        assert_eq!(world.community().len(), 1);
+
+        let root = world.spawn_node(BazBuilder);
+        let a = world.spawn_child(root, BarBuilder {}).unwrap();
+        let b = world.spawn_child(a, BarBuilder {}).unwrap();
+        let c = world.spawn_child(root, BarBuilder {}).unwrap();
+        world.despawn_with(a, DespawnPolicy::Reparent).unwrap();
+        assert_eq!(world.parent_of(b), Some(root));
+        assert_eq!(world.children_of(root).collect::<Vec<_>>(), [b, c]);
+    }
+
+    #[test]
+    fn traversal() {
+        let mut world = World::new();
+        world.register_trait::<Foo<u32>, dyn Process, _>(|x| Box::new(x));
+        let root = world.spawn_node(BazBuilder);
+        let a = world.spawn_child(root, BarBuilder {}).unwrap();
+        let a1 = world.spawn_child(a, BazBuilder).unwrap();
+        let b = world
+            .spawn_child(
+                root,
+                FooBuilder {
+                    x: Useless,
+                    y: 0,
+                    z: 0,
+                    bar: 0u32,
+                },
+            )
+            .unwrap();
+        let c = world.spawn_child(root, BazBuilder).unwrap();
+        let a11 = world.spawn_child(a1, BarBuilder {}).unwrap();
+
+        let dfs: Vec<_> = world.descendants(root).collect();
+        assert_eq!(dfs, [a, a1, a11, b, c]);
+        let bfs: Vec<_> = world.descendants_bfs(root).collect();
+        assert_eq!(bfs, [a, b, c, a1, a11]);
+        assert_eq!(world.ancestors(a11).collect::<Vec<_>>(), [a1, a, root]);
+        assert_eq!(world.ancestors(root).count(), 0);
+        assert_eq!(world.siblings(b).collect::<Vec<_>>(), [a, c]);
+        assert_eq!(world.siblings(root).count(), 0);
+
+        let bazs: Vec<_> = world.descendants(root).of_type::<Baz>().collect();
+        assert_eq!(bazs, [a1, c]);
+        assert_eq!(world.ancestors(a11).of_type::<Bar>().next(), Some(a));
+        let processes: Vec<_> = world
+            .descendants_bfs(root)
+            .implementing::<dyn Process>()
+            .collect();
+        assert_eq!(processes, [b]);
+        assert_eq!(world.descendants(root).of_type::<Enemy>().count(), 0);
+
+        world.despawn_node(root).unwrap();
+        assert_eq!(world.descendants(root).count(), 0);
+        assert_eq!(world.ancestors(a).count(), 0);
+    }
+
+    #[test]
+    fn paths() {
+        let mut world = World::new();
+        let level = world.spawn_node(BazBuilder);
+        let player = world.spawn_child(level, BazBuilder).unwrap();
+        let weapon = world.spawn_child(player, BarBuilder {}).unwrap();
+        let enemy = world.spawn_child(level, BazBuilder).unwrap();
+        world.set_name(level, "level").unwrap();
+        world.set_name(player, "player").unwrap();
+        world.set_name(weapon, "weapon").unwrap();
+
+        assert_eq!(world.name_of(player), Some("player"));
+        assert_eq!(world.get_node_by_path(level, "player/weapon"), Some(weapon));
+        assert_eq!(world.get_node_by_path(level, "./player/"), Some(player));
+        assert_eq!(world.get_node_by_path(weapon, "../../player"), Some(player));
+        assert_eq!(world.get_node_by_path(level, ".."), None);
+        assert_eq!(world.get_node_by_path(level, "enemy"), None);
+        assert_eq!(world.path_of(weapon).as_deref(), Some("player/weapon"));
+        assert_eq!(world.path_of(level).as_deref(), Some(""));
+        assert_eq!(world.path_of(enemy), None);
+
+        assert_eq!(world.set_name(enemy, "a/b"), Err(NecsError::InvalidName));
+        assert_eq!(
+            world.set_name(enemy, "player"),
+            Err(NecsError::NameTaken(player))
+        );
+        world.set_name(player, "player").unwrap();
+
+        // Names stay unique when moving nodes around.
+        let other_weapon = world.spawn_child(level, BarBuilder {}).unwrap();
+        world.set_name(other_weapon, "weapon").unwrap();
+        assert_eq!(
+            world.set_parent(other_weapon, player),
+            Err(NecsError::NameTaken(weapon))
+        );
+        world.set_name(weapon, "player").unwrap();
+        assert_eq!(world.despawn_with(player, DespawnPolicy::Reparent), Ok(()));
+        assert_eq!(world.get_node_by_path(level, "player"), Some(weapon));
+        world.remove_name(weapon).unwrap();
+        assert_eq!(world.name_of(weapon), None);
+    }
+
+    #[test]
+    fn child_order() {
+        let mut world = World::new();
+        let root = world.spawn_node(BazBuilder);
+        let [a, b, c] = [(); 3].map(|_| world.spawn_child(root, BazBuilder).unwrap());
+        let children = |world: &World| world.children_of(root).collect::<Vec<_>>();
+        assert_eq!(children(&world), [a, b, c]);
+        assert_eq!(world.child_index(c), Some(2));
+        assert_eq!(world.child_index(root), None);
+
+        world.move_child(root, c, 0).unwrap();
+        assert_eq!(children(&world), [c, a, b]);
+        world.move_child(root, c, 10).unwrap();
+        assert_eq!(children(&world), [a, b, c]);
+        assert_eq!(
+            world.move_child(a, b, 0),
+            Err(NecsError::NotChild { id: b, parent: a })
+        );
+
+        let d = world.spawn_node(BazBuilder);
+        world.insert_child_at(root, d, 1).unwrap();
+        assert_eq!(children(&world), [a, d, b, c]);
+        let e = world.spawn_child(d, BazBuilder).unwrap();
+        let f = world.spawn_child(d, BazBuilder).unwrap();
+
+        // Despawning keeps the order of the remaining children.
+        world.despawn_node(b).unwrap();
+        assert_eq!(children(&world), [a, d, c]);
+        world.despawn_with(d, DespawnPolicy::Reparent).unwrap();
+        assert_eq!(children(&world), [a, e, f, c]);
+    }
+
+    #[test]
+    fn propagate() {
+        let mut world = World::new();
+        let spatial = |offset| SpatialBuilder {
+            offset: Offset(offset),
+            position: Position(0.0),
+        };
+        let root = world.spawn_node(spatial(1.0));
+        let a = world.spawn_child(root, spatial(2.0)).unwrap();
+        let b = world.spawn_child(a, spatial(4.0)).unwrap();
+        let gap = world.spawn_child(root, BazBuilder).unwrap();
+        let c = world.spawn_child(gap, spatial(8.0)).unwrap();
+
+        let calls = std::cell::Cell::new(0);
+        let combine = |parent: Option<&Position>, offset: &Offset| {
+            calls.set(calls.get() + 1);
+            Position(parent.map_or(0.0, |parent| parent.0) + offset.0)
+        };
+        let propagate = |world: &mut World| {
+            calls.set(0);
+            world
+                .propagate_changed::<Offset, Position, _>(combine)
+                .unwrap();
+            calls.get()
+        };
+        let position = |world: &World, id| world.get_node::<Spatial>(id).position.0;
+
+        assert_eq!(propagate(&mut world), 4);
+        assert_eq!(position(&world, root), 1.0);
+        assert_eq!(position(&world, b), 7.0);
+        // Nodes without the fields break the chain.
+        assert_eq!(position(&world, c), 8.0);
+        // Unless asked to skip unchanged subtrees, every node is recomputed.
+        calls.set(0);
+        world.propagate::<Offset, Position, _>(combine).unwrap();
+        assert_eq!(calls.get(), 4);
+
+        // Unchanged subtrees are skipped.
+        assert_eq!(propagate(&mut world), 0);
+        *world.get_node::<Spatial>(a).offset = Offset(3.0);
+        assert_eq!(propagate(&mut world), 2);
+        assert_eq!(position(&world, b), 8.0);
+        world.set_parent(c, b).unwrap();
+        assert_eq!(propagate(&mut world), 1);
+        assert_eq!(position(&world, c), 16.0);
+
+        let node = world.get_node_resilient::<dyn Node>(b);
+        assert_eq!(
+            world.propagate::<Offset, Position, _>(|_, _| Position(0.0)),
+            Err(NecsError::AlreadyBorrowed(b))
+        );
+        drop(node);
+        // A failed pass writes nothing and keeps what was learned before.
+        assert_eq!(position(&world, root), 1.0);
+        assert_eq!(propagate(&mut world), 0);
+
+        // Despawned nodes are forgotten, and their children recomputed.
+        world.despawn_node(b).unwrap();
+        assert_eq!(propagate(&mut world), 1);
+        assert_eq!(position(&world, c), 8.0);
+
+        // Fields changed while a node was taken are noticed.
+        let mut node = world.take_node::<Spatial>(root).unwrap();
+        node.position = Position(-5.0);
+        world.reinsert_node(root, node).unwrap();
+        assert_eq!(propagate(&mut world), 2);
+        assert_eq!(position(&world, root), 1.0);
+
+        // A different combine function is applied by a full pass.
+        world
+            .propagate::<Offset, Position, _>(|_, offset| Position(offset.0 * 100.0))
+            .unwrap();
+        assert_eq!(position(&world, root), 100.0);
+        assert_eq!(position(&world, a), 300.0);
+    }
+
+    #[test]
+    fn events() {
+        let mut world = World::new();
+        world.register_trait::<Button, dyn Handles<Click>, _>(|x| Box::new(x));
+        let window = world.spawn_node(BazBuilder);
+        let panel = world.spawn_child(window, BarBuilder {}).unwrap();
+        let button = world
+            .spawn_child(panel, ButtonBuilder { clicks: 0 })
+            .unwrap();
+        world
+            .add_event_handler(window, |event: &mut Event<'_, Click>| {
+                let step = (event.current(), event.phase());
+                event.path.push(step);
+            })
+            .unwrap();
+
+        let mut click = Click::default();
+        world.dispatch_event(button, &mut click).unwrap();
+        assert_eq!(
+            click.path,
+            [
+                (window, EventPhase::Capture),
+                (button, EventPhase::Target),
+                (window, EventPhase::Bubble),
+            ]
+        );
+        assert_eq!(*world.get_node::<Button>(button).clicks, 1);
+
+        // Stopping propagation keeps the event from bubbling up to the window.
+        world
+            .add_event_handler(panel, |event: &mut Event<'_, Click>| {
+                if event.phase() == EventPhase::Bubble {
+                    event.stop_propagation();
+                }
+            })
+            .unwrap();
+        let mut click = Click::default();
+        world.dispatch_event(button, &mut click).unwrap();
+        assert_eq!(click.path.len(), 2);
+
+        world.remove_event_handlers::<Click>(window);
+        let mut click = Click::default();
+        world.dispatch_event(panel, &mut click).unwrap();
+        assert!(click.path.is_empty());
+
+        world.despawn_node(button).unwrap();
+        assert_eq!(
+            world.dispatch_event(button, &mut Click::default()),
+            Err(NecsError::StaleId(button))
+        );
+    }
+
+    #[test]
+    fn events_with_unavailable_nodes() {
+        let mut world = World::new();
+        world.register_trait::<Button, dyn Handles<Click>, _>(|x| Box::new(x));
+        let outer = world.spawn_node(ButtonBuilder { clicks: 0 });
+        let inner = world
+            .spawn_child(outer, ButtonBuilder { clicks: 0 })
+            .unwrap();
+
+        // A borrowed node on the path stops the dispatch before any handler runs.
+        let node = world.get_node_resilient::<dyn Node>(outer);
+        let mut click = Click::default();
+        assert_eq!(
+            world.dispatch_event(inner, &mut click),
+            Err(NecsError::AlreadyBorrowed(outer))
+        );
+        drop(node);
+        assert!(click.path.is_empty());
+        assert_eq!(*world.get_node::<Button>(inner).clicks, 0);
+
+        // Taken nodes are skipped.
+        let taken = world.take_node::<Button>(outer).unwrap();
+        let mut click = Click::default();
+        world.dispatch_event(inner, &mut click).unwrap();
+        assert_eq!(click.path, [(inner, EventPhase::Target)]);
+        world.reinsert_node(outer, taken).unwrap();
+    }
+
+    #[test]
+    fn lifecycle() {
+        let take_log = || LIFECYCLE_LOG.take();
+        let mut world = World::new();
+        world.register_trait::<Tracked, dyn Lifecycle, _>(|x| Box::new(x));
+        let a = world.spawn_node(TrackedBuilder { tag: 'a' });
+        let b = world.spawn_child(a, TrackedBuilder { tag: 'b' }).unwrap();
+        world.spawn_child(b, TrackedBuilder { tag: 'c' }).unwrap();
+        world.spawn_child(a, BazBuilder).unwrap();
+        assert_eq!(take_log(), "+a!a+b!b+c!c");
+
+        // Ready fires on children before their parent.
+        world.clone_node_recursive::<Tracked>(b).unwrap();
+        assert_eq!(take_log(), "+b+c!c!b");
+
+        let d = world.spawn_node(TrackedBuilder { tag: 'd' });
+        take_log();
+        world.set_parent(b, d).unwrap();
+        assert_eq!(take_log(), "-c-b+b+c");
+        world.move_child(d, b, 0).unwrap();
+        assert_eq!(take_log(), "");
+
+        world.despawn_node(d).unwrap();
+        assert_eq!(take_log(), "-c-b-d+b+c");
+        world.despawn_recursive(b).unwrap();
+        assert_eq!(take_log(), "-c-b");
+    }
+
+    #[test]
+    fn lifecycle_with_borrowed_nodes() {
+        let take_log = || LIFECYCLE_LOG.take();
+        let mut world = World::new();
+        world.register_trait::<Tracked, dyn Lifecycle, _>(|x| Box::new(x));
+        let a = world.spawn_node(TrackedBuilder { tag: 'a' });
+        let b = world.spawn_child(a, TrackedBuilder { tag: 'b' }).unwrap();
+        let c = world.spawn_node(TrackedBuilder { tag: 'c' });
+        take_log();
+
+        // Structural changes whose callbacks would reach a borrowed node fail
+        // without changing anything.
+        let node = world.get_node_resilient::<dyn Node>(b);
+        assert_eq!(world.despawn_node(a), Err(NecsError::AlreadyBorrowed(b)));
+        assert_eq!(world.set_parent(a, c), Err(NecsError::AlreadyBorrowed(b)));
+        assert_eq!(world.remove_parent(b), Err(NecsError::AlreadyBorrowed(b)));
+        assert_eq!(
+            world.despawn_with(a, DespawnPolicy::Reparent),
+            Err(NecsError::AlreadyBorrowed(b))
+        );
+        drop(node);
+        assert_eq!(take_log(), "");
+        assert!(world.contains(a));
+        assert_eq!(world.parent_of(a), None);
+        assert_eq!(world.parent_of(b), Some(a));
+
+        world.despawn_node(a).unwrap();
+        assert_eq!(take_log(), "-b-a+b");
+
+        // Unregistering a type fails before despawning anything if a node of
+        // another type under one of its nodes is borrowed.
+        let first = world.spawn_node(ButtonBuilder { clicks: 0 });
+        let second = world.spawn_node(ButtonBuilder { clicks: 0 });
+        let d = world
+            .spawn_child(second, TrackedBuilder { tag: 'd' })
+            .unwrap();
+        take_log();
+        let node = world.get_node_resilient::<dyn Node>(d);
+        assert_eq!(
+            world.unregister_node::<Button>(),
+            Err(NecsError::AlreadyBorrowed(d))
+        );
+        drop(node);
+        assert_eq!(take_log(), "");
+        assert!(world.contains(first) && world.contains(second));
+        assert_eq!(world.parent_of(d), Some(second));
+
+        world.unregister_node::<Button>().unwrap();
+        assert_eq!(take_log(), "-d+d");
+        assert!(!world.contains(first) && !world.contains(second));
+    }
+
+    #[test]
+    fn active_nodes() {
+        let mut world = World::new();
+        let enemy = |health| EnemyBuilder {
+            health,
+            position: (0.0, 0.0),
+        };
+        let level = world.spawn_node(BazBuilder);
+        let section = world.spawn_child(level, BazBuilder).unwrap();
+        let a = world.spawn_child(section, enemy(1)).unwrap();
+        let b = world.spawn_child(level, enemy(2)).unwrap();
+        let menu = world.spawn_node(enemy(3));
+        let health = |world: &World| {
+            let mut health: Vec<_> = world
+                .get_nodes::<Enemy>()
+                .iter()
+                .map(|enemy| *enemy.health)
+                .collect();
+            health.sort();
+            health
+        };
+        assert_eq!(health(&world), [1, 2, 3]);
+
+        // Disabling a node disables its whole subtree.
+        world.set_enabled(section, false).unwrap();
+        assert!(!world.is_active(a));
+        assert_eq!(health(&world), [2, 3]);
+        assert_eq!(world.get_node_ids::<Enemy>().len(), 2);
+        assert_eq!(world.get_all_node_ids::<Enemy>().len(), 3);
+        assert_eq!(world.get_all_nodes::<Enemy>().len(), 3);
+        assert_eq!(world.descendants(level).active().count(), 1);
+        assert_eq!(*world.get_node::<Enemy>(a).health, 1);
+
+        world
+            .set_process_mode(menu, ProcessMode::WhenPaused)
+            .unwrap();
+        assert_eq!(health(&world), [2]);
+        world.set_paused(true);
+        assert_eq!(health(&world), [3]);
+        world.set_process_mode(level, ProcessMode::Always).unwrap();
+        assert_eq!(health(&world), [2, 3]);
+        world.set_enabled(section, true).unwrap();
+        assert_eq!(health(&world), [1, 2, 3]);
+
+        world.set_paused(false);
+        world.despawn_node(menu).unwrap();
+        world.set_process_mode(level, ProcessMode::Inherit).unwrap();
+        assert!(world.is_active(a) && world.is_active(b));
+    }
+
+    #[test]
+    fn validate() {
+        let mut world = World::new();
+        let a = world.spawn_node(BazBuilder);
+        let b = world.spawn_child(a, BazBuilder).unwrap();
+        let c = world.spawn_child(b, BazBuilder).unwrap();
+        assert_eq!(
+            world.set_parent(a, c),
+            Err(NecsError::WouldCycle { id: a, parent: c })
+        );
+        assert_eq!(
+            world.set_parent(a, a),
+            Err(NecsError::WouldCycle { id: a, parent: a })
+        );
+        assert!(world.validate().is_ok());
+
+        // Relations can only become inconsistent by editing them directly.
+        world.edit_community(|community| {
+            community.insert(a.instance, Relations::new(Some(c.instance)));
+            community.remove(&c.instance);
+        });
+        let issues = world.validate().issues;
+        assert_eq!(issues.len(), 4);
+        assert!(issues.contains(&HierarchyIssue::MissingRelations(c)));
+        assert!(issues.contains(&HierarchyIssue::NotAChildOfParent { id: b, parent: a }));
+        assert!(issues.contains(&HierarchyIssue::NotAChildOfParent { id: a, parent: c }));
+        assert!(issues.contains(&HierarchyIssue::ChildOfAnotherParent { id: b, child: c }));
+
+        world.edit_community(|community| {
+            community.insert(c.instance, Relations::new(Some(b.instance)))
+        });
+        let issues = world.validate().issues;
+        assert!(issues.contains(&HierarchyIssue::Cycle(a)));
+        assert!(!issues.contains(&HierarchyIssue::MissingRelations(c)));
+    }
+
+    #[test]
+    fn iter_nodes() {
+        let mut world = World::new();
+        let ids = world.spawn_batch((1..=3).map(|health| EnemyBuilder {
+            health,
+            position: (0.0, 0.0),
+        }));
+
+        // Only the yielded node is borrowed, so others can still be accessed.
+        for enemy in world.iter_nodes::<Enemy>() {
+            for (&id, health) in ids.iter().zip(1..) {
+                let other = world.try_get_node::<Enemy>(id);
+                assert_eq!(other.is_ok(), *enemy.health != health);
+            }
+        }
+        let mut total = 0;
+        world.for_each_node::<Enemy>(|enemy| total += *enemy.health);
+        assert_eq!(total, 6);
+
+        world.set_enabled(ids[0], false).unwrap();
+        assert_eq!(world.iter_nodes::<Enemy>().count(), 2);
+        assert_eq!(world.iter_all_nodes::<Enemy>().len(), 3);
+        assert_eq!(world.iter_nodes::<Corpse>().count(), 0);
+    }
+
+    #[test]
+    fn components() {
+        let mut world = World::new();
+        let enemy = world.spawn_node(EnemyBuilder {
+            health: 1,
+            position: (1.0, 0.0),
+        });
+        let corpse = world.spawn_node(CorpseBuilder {
+            decay: 0.0,
+            position: (2.0, 0.0),
+        });
+        world.spawn_node(BazBuilder);
+
+        // Every node storing a (f32, f32) is reached, whatever its type.
+        let mut owners = Vec::new();
+        for (id, mut position) in world.components::<(f32, f32)>() {
+            position.1 += 1.0;
+            owners.push(id);
+        }
+        owners.sort_by_key(|&id| id == corpse);
+        assert_eq!(owners, [enemy, corpse]);
+        assert_eq!(*world.get_node::<Enemy>(enemy).position, (1.0, 1.0));
+        assert_eq!(*world.get_node::<Corpse>(corpse).position, (2.0, 1.0));
+        assert_eq!(world.components::<u64>().count(), 0);
+
+        world.set_enabled(corpse, false).unwrap();
+        assert_eq!(world.components::<(f32, f32)>().count(), 1);
+        assert_eq!(world.all_components::<(f32, f32)>().count(), 2);
+    }
+
+    #[test]
+    fn components_with_borrowed_nodes() {
+        let mut world = World::new();
+        let enemy = world.spawn_node(EnemyBuilder {
+            health: 1,
+            position: (1.0, 0.0),
+        });
+        let corpse = world.spawn_node(CorpseBuilder {
+            decay: 0.0,
+            position: (2.0, 0.0),
+        });
+        let button = world.spawn_node(ButtonBuilder { clicks: 0 });
+
+        // Fields can be read while an unrelated node is borrowed.
+        let button = world.get_node::<Button>(button);
+        let sum: f32 = world
+            .all_components::<(f32, f32)>()
+            .map(|(_, position)| position.0)
+            .sum();
+        assert_eq!(sum, 3.0);
+        *button.clicks += 1;
+
+        // A yielded field keeps its node borrowed, but only that node.
+        let (_, position) = world
+            .components::<(f32, f32)>()
+            .find(|&(id, _)| id == enemy)
+            .unwrap();
+        assert!(matches!(
+            world.try_get_node::<Enemy>(enemy),
+            Err(NecsError::AlreadyBorrowed(id)) if id == enemy
+        ));
+        assert_eq!(*world.get_node::<Corpse>(corpse).position, (2.0, 0.0));
+        assert_eq!(*position, (1.0, 0.0));
+        drop(position);
+        assert!(world.try_get_node::<Enemy>(enemy).is_ok());
+    }
+
+    #[test]
+    fn iter_nodes_resilient() {
+        let mut world = World::new();
+        world.register_trait::<Enemy, dyn Describe, _>(|x| Box::new(x));
+        world.register_trait::<Corpse, dyn Describe, _>(|x| Box::new(x));
+        let enemy = world.spawn_node(EnemyBuilder {
+            health: 3,
+            position: (0.0, 0.0),
+        });
+        let corpse = world.spawn_node(CorpseBuilder {
+            decay: 0.5,
+            position: (0.0, 0.0),
+        });
+        let taken = world.spawn_node(EnemyBuilder {
+            health: 1,
+            position: (0.0, 0.0),
+        });
+        world.take_node::<Enemy>(taken).unwrap();
+        world.spawn_node(BazBuilder);
+
+        // Every node type registered for the trait is reached in one loop.
+        let mut descriptions: Vec<(NodeId, String)> = world
+            .iter_nodes_resilient::<dyn Describe>()
+            .map(|(id, node)| (id, node.describe()))
+            .collect();
+        descriptions.sort_by_key(|&(id, _)| id == corpse);
+        assert_eq!(
+            descriptions,
+            [
+                (enemy, "enemy 3".to_string()),
+                (corpse, "corpse 0.5".to_string())
+            ]
+        );
+        assert_eq!(world.get_nodes_resilient::<dyn Node>().len(), 3);
+        assert_eq!(world.iter_nodes_resilient::<dyn Process>().count(), 0);
+
+        world.set_enabled(enemy, false).unwrap();
+        let nodes = world.get_nodes_resilient::<dyn Describe>();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].1.describe(), "corpse 0.5");
+    }
+
+    #[test]
+    fn query_nodes() {
+        use std::collections::HashSet;
+
+        let mut world = World::new();
+        world.register_trait::<Enemy, dyn Describe, _>(|x| Box::new(x));
+        let spatial = world.spawn_node(SpatialBuilder {
+            offset: Offset(0.0),
+            position: Position(0.0),
+        });
+        let weak = world.spawn_node(EnemyBuilder {
+            health: 1,
+            position: (0.0, 0.0),
+        });
+        let strong = world.spawn_node(EnemyBuilder {
+            health: 9,
+            position: (0.0, 0.0),
+        });
+        let corpse = world.spawn_node(CorpseBuilder {
+            decay: 0.0,
+            position: (0.0, 0.0),
+        });
+
+        let ids = |query: NodeQuery| query.ids().collect::<HashSet<NodeId>>();
+        assert_eq!(
+            ids(world.query_nodes().with_ext::<(f32, f32)>()),
+            HashSet::from([weak, strong, corpse])
+        );
+        assert_eq!(
+            ids(world.query_nodes().without_ext::<(f32, f32)>()),
+            HashSet::from([spatial])
+        );
+        assert_eq!(
+            ids(world
+                .query_nodes()
+                .with_ext::<(f32, f32)>()
+                .implementing::<dyn Describe>()),
+            HashSet::from([weak, strong])
+        );
+        assert!(ids(world.query_nodes().with_ext::<u64>()).is_empty());
+        assert_eq!(ids(world.query_nodes().without_ext::<u64>()).len(), 4);
+
+        // Predicates see every node passing the type-level filters.
+        let strong_enemies: Vec<u32> = world
+            .query_nodes()
+            .filter(|id| *world.get_node::<Enemy>(id).health > 5)
+            .of_type::<Enemy>()
+            .nodes::<Enemy>()
+            .map(|enemy| *enemy.health)
+            .collect();
+        assert_eq!(strong_enemies, [9]);
+        let described: Vec<String> = world
+            .query_nodes()
+            .filter(|id| id != weak)
+            .nodes_resilient::<dyn Describe>()
+            .map(|(_, node)| node.describe())
+            .collect();
+        assert_eq!(described, ["enemy 9"]);
+
+        // Taken nodes are skipped.
+        let builder = world.take_node::<Enemy>(strong).unwrap();
+        assert_eq!(
+            ids(world.query_nodes().with_ext::<(f32, f32)>()),
+            HashSet::from([weak, corpse])
+        );
+        world.reinsert_node(strong, builder).unwrap();
+
+        world.set_enabled(corpse, false).unwrap();
+        assert_eq!(world.query_nodes().of_type::<Corpse>().ids().count(), 0);
+        assert_eq!(
+            ids(world.query_nodes().of_type::<Corpse>().include_inactive()),
+            HashSet::from([corpse])
+        );
+    }
+
+    #[test]
+    fn query() {
+        let mut world = World::new();
+        let a = world.spawn_node(SpatialBuilder {
+            offset: Offset(1.0),
+            position: Position(0.0),
+        });
+        let b = world.spawn_node(SpatialBuilder {
+            offset: Offset(2.0),
+            position: Position(5.0),
+        });
+        world.spawn_node(EnemyBuilder {
+            health: 1,
+            position: (0.0, 0.0),
+        });
+
+        for (_, (position, offset)) in world.query::<(&mut Position, &Offset)>() {
+            position.0 += offset.0;
+        }
+        assert_eq!(world.get_node::<Spatial>(a).position, &Position(1.0));
+        assert_eq!(world.get_node::<Spatial>(b).position, &Position(7.0));
+
+        // Nodes lacking one of the components are skipped.
+        assert_eq!(world.query::<(&Position, &(f32, f32))>().count(), 0);
+        assert_eq!(world.query::<(&Offset, &u64)>().count(), 0);
+        let mut ids: Vec<NodeId> = world.query::<(&Offset,)>().map(|(id, _)| id).collect();
+        ids.sort_by_key(|&id| id == b);
+        assert_eq!(ids, [a, b]);
+
+        world.set_enabled(b, false).unwrap();
+        assert_eq!(world.query::<(&Offset, &Position)>().count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "alias")]
+    fn query_aliasing() {
+        let mut world = World::new();
+        world.spawn_node(SpatialBuilder {
+            offset: Offset(1.0),
+            position: Position(0.0),
+        });
+        world.query::<(&mut Position, &Position)>().count();
+    }
+
     mod flamegraph_test {
         use necs::node;
 